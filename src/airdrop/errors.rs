@@ -11,6 +11,9 @@ pub enum AirDropError {
     #[error("Failed to parse AirDrop list file {0} with error {1}")]
     AirDropListFileWrongFormat(String, String),
 
+    #[error("Invalid line {1} in AirDrop list file {0}: {2}")]
+    AirDropListInvalidLine(String, usize, String),
+
     #[error("Cannot use number and airdrop feature at the same time")]
     CannotUseNumberAndAirdropFeatureAtTheSameTime,
 
@@ -25,4 +28,10 @@ pub enum AirDropError {
 
     #[error("Overflow during sync of results and targets for address {0}")]
     OverflowDuringSyncOfResultsAndTargetsForAddress(String),
+
+    #[error("Candy machine is wrapped by candy guard {0}, use --unwrap to airdrop from it")]
+    CandyMachineWrapped(String),
+
+    #[error("Payer is not the authority of candy guard {0}")]
+    InvalidCandyGuardAuthority(String),
 }
@@ -1,6 +1,9 @@
 pub mod errors;
 pub mod process;
+pub mod reconcile;
 pub mod structs;
+pub mod unwrap;
 pub mod utils;
 
 pub use process::*;
+pub use reconcile::*;
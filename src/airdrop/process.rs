@@ -3,21 +3,33 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
 use anyhow::Result;
 use console::style;
+use solana_client::client_error::ClientError;
 use tokio::sync::Semaphore;
 
 use crate::{
     airdrop::{
         errors::AirDropError,
-        structs::{AirDropTargets, TransactionResult},
-        utils::{load_airdrop_list, load_airdrop_results, write_airdrop_results},
+        structs::{
+            AirDropRecipientInfos, AirDropResults, AirDropTargets, PendingTransaction,
+            RecipientInfo, SerdePubkey, TransactionResult,
+        },
+        unwrap::{
+            get_wrapping_candy_guard, print_rewrap_result, rewrap_candy_guard, unwrap_candy_guard,
+        },
+        utils::{
+            append_pending_transaction, clear_pending_transactions, load_airdrop_list,
+            load_airdrop_results, write_airdrop_results,
+        },
     },
     cache::load_cache,
     candy_machine::{CANDY_MACHINE_ID, *},
     common::*,
-    mint::mint,
+    config::SugarConfig,
+    mint::{build_mint_transaction, send_mint_transaction},
+    mints::{mint_registry_path, record_mint, MintRecord},
     pdas::get_metadata_pda,
     utils::*,
 };
@@ -28,7 +40,9 @@ pub struct AirdropArgs {
     pub cache: String,
     pub candy_machine: Option<String>,
     pub airdrop_list: String,
+    pub results: String,
     pub priority_fee: u64,
+    pub unwrap: bool,
 }
 
 pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
@@ -36,17 +50,21 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
     let client = setup_client(&sugar_config)?;
     let program = client.program(CANDY_MACHINE_ID);
 
-    let mut airdrop_list: AirDropTargets = load_airdrop_list(args.airdrop_list)?;
+    let (mut airdrop_list, recipients) = load_airdrop_list(args.airdrop_list)?;
 
     // load_airdrop_results syncs airdrop_list and airdrop_results in case of rerun failures
     let airdrop_total_original = airdrop_list.iter().fold(0, |acc, x| acc + x.1);
-    let airdrop_results = Arc::new(Mutex::new(load_airdrop_results(&mut airdrop_list)?));
+    let airdrop_results = Arc::new(Mutex::new(load_airdrop_results(
+        &mut airdrop_list,
+        &args.results,
+    )?));
     let airdrop_total = airdrop_list.iter().fold(0, |acc, x| acc + x.1);
 
     if airdrop_total_original != airdrop_total {
         print!(
-            "Skipping {} mints due to existing transactions in airdrop_results.json",
-            airdrop_total_original - airdrop_total
+            "Skipping {} mints due to existing transactions in {}",
+            airdrop_total_original - airdrop_total,
+            args.results
         );
     }
 
@@ -79,13 +97,105 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let candy_machine_state = Arc::new(get_candy_machine_state(&sugar_config, &candy_pubkey)?);
+    // adds back a candy guard left unwrapped by a previous run
+    let rewrap = rewrap_candy_guard(&sugar_config, &args.results, args.priority_fee);
+    if rewrap.is_err() {
+        pb.finish_and_clear();
+        print_rewrap_result(&rewrap);
+    }
+    rewrap?;
+
+    let mut candy_machine_state = get_candy_machine_state(&sugar_config, &candy_pubkey)?;
+    let candy_guard = get_wrapping_candy_guard(&sugar_config, &candy_machine_state)?;
+
+    if let Some(candy_guard) = candy_guard {
+        if !args.unwrap {
+            return Err(AirDropError::CandyMachineWrapped(candy_guard.to_string()).into());
+        }
+
+        pb.set_message("Removing candy guard...");
+        unwrap_candy_guard(
+            &sugar_config,
+            candy_guard,
+            candy_pubkey,
+            &args.results,
+            args.priority_fee,
+        )?;
+        candy_machine_state = get_candy_machine_state(&sugar_config, &candy_pubkey)?;
+    }
+
+    let candy_machine_state = Arc::new(candy_machine_state);
     let (_, collection_metadata) =
         get_metadata_pda(&candy_machine_state.collection_mint, &program)?;
     let collection_update_authority = collection_metadata.update_authority;
 
     pb.finish_with_message("Done");
 
+    if let Some(candy_guard) = candy_guard {
+        println!(
+            "\n{}{} {}",
+            UNWRAP_EMOJI,
+            style("Candy guard removed for the airdrop:").bold(),
+            candy_guard
+        );
+    }
+
+    let config = Arc::new(sugar_config);
+
+    if candy_guard.is_some() {
+        // the candy guard is added back even if the airdrop is interrupted
+        let config = config.clone();
+        let results_path = args.results.clone();
+        let priority_fee = args.priority_fee;
+
+        set_interrupt_hook(Box::new(move || {
+            println!("\n{}Adding the candy guard back...", WRAP_EMOJI);
+            print_rewrap_result(&rewrap_candy_guard(&config, &results_path, priority_fee));
+        }));
+    }
+
+    let result = airdrop_mints(
+        config.clone(),
+        candy_pubkey,
+        candy_machine_state,
+        collection_update_authority,
+        airdrop_list,
+        &recipients,
+        airdrop_results,
+        &args.results,
+        &mint_registry_path(&args.cache),
+        args.priority_fee,
+    )
+    .await;
+
+    if candy_guard.is_some() {
+        clear_interrupt_hook();
+        let rewrap = rewrap_candy_guard(&config, &args.results, args.priority_fee);
+        print_rewrap_result(&rewrap);
+        result?;
+        rewrap?;
+        return Ok(());
+    }
+
+    result
+}
+
+/// Mints the NFTs of the airdrop list.
+#[allow(clippy::too_many_arguments)]
+async fn airdrop_mints(
+    config: Arc<SugarConfig>,
+    candy_pubkey: Pubkey,
+    candy_machine_state: Arc<CandyMachine>,
+    collection_update_authority: Pubkey,
+    mut airdrop_list: AirDropTargets,
+    recipients: &AirDropRecipientInfos,
+    airdrop_results: Arc<Mutex<AirDropResults>>,
+    results_path: &str,
+    registry: &Path,
+    priority_fee: u64,
+) -> Result<()> {
+    let airdrop_total = airdrop_list.iter().fold(0, |acc, x| acc + x.1);
+
     println!(
         "\n{} {}Minting from candy machine",
         style("[2/2]").bold().dim(),
@@ -100,13 +210,12 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
         );
     }
 
-    info!("Minting NFT from candy machine: {}", &candy_machine_id);
+    info!("Minting NFT from candy machine: {}", &candy_pubkey);
     info!("Candy machine program id: {:?}", CANDY_MACHINE_ID);
 
     let pb = progress_bar_with_style(airdrop_total);
     let mut tasks = Vec::new();
     let semaphore = Arc::new(Semaphore::new(10));
-    let config = Arc::new(sugar_config);
 
     for (address, num) in airdrop_list.drain() {
         for _i in 0..num {
@@ -114,50 +223,28 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
             let config = config.clone();
             let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
             let candy_machine_state = candy_machine_state.clone();
-            let target = address.0;
+            let results_path = results_path.to_string();
+            let registry = registry.to_path_buf();
+            let recipient = recipients.get(&address).cloned().unwrap_or_default();
             let pb = pb.clone();
 
             // Start tasks
             tasks.push(tokio::spawn(async move {
                 let _permit = permit;
-                let res = mint(
-                    config,
+                let res = airdrop_mint(
+                    &config,
                     candy_pubkey,
-                    candy_machine_state,
+                    &candy_machine_state,
                     collection_update_authority,
-                    target,
-                    args.priority_fee,
-                )
-                .await;
+                    address,
+                    recipient,
+                    priority_fee,
+                    &results,
+                    &results_path,
+                    &registry,
+                );
                 pb.inc(1);
 
-                let mut results = results.lock().unwrap();
-                results.entry(address).or_insert_with(Vec::new);
-                let signatures = results.get_mut(&address).unwrap();
-
-                match &res {
-                    Ok((signature, _)) => {
-                        signatures.push(TransactionResult {
-                            signature: signature.to_string(),
-                            status: true,
-                        });
-                    }
-                    Err(err) => {
-                        // Assume timeouts succeed to avoid sending double to a recipient.
-                        if err.to_string().contains("Transaction was not confirmed in") {
-                            signatures.push(TransactionResult {
-                                signature: "RPC timeout: unknown if transaction succeeded"
-                                    .to_string(),
-                                status: true,
-                            });
-                        }
-                        signatures.push(TransactionResult {
-                            signature: err.to_string(),
-                            status: false,
-                        });
-                    }
-                }
-
                 res
             }));
         }
@@ -174,7 +261,14 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
         }
     }
 
-    write_airdrop_results(&airdrop_results.lock().unwrap())?;
+    write_airdrop_results(&airdrop_results.lock().unwrap(), results_path)?;
+    // all recorded transactions are now part of the results file
+    clear_pending_transactions(results_path)?;
+
+    set_output("minted", airdrop_total - error_count);
+    set_output("failed", error_count);
+    set_output("results", results_path);
+
     if error_count > 0 {
         pb.abandon_with_message(format!(
             "{} {} items failed.",
@@ -193,3 +287,137 @@ pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Mints an NFT to the target address, recording the transaction before it is
+/// sent and its outcome in the results.
+#[allow(clippy::too_many_arguments)]
+fn airdrop_mint(
+    config: &SugarConfig,
+    candy_machine_id: Pubkey,
+    candy_machine_state: &CandyMachine,
+    collection_update_authority: Pubkey,
+    address: SerdePubkey,
+    recipient: RecipientInfo,
+    priority_fee: u64,
+    results: &Mutex<AirDropResults>,
+    results_path: &str,
+    registry: &Path,
+) -> Result<Signature> {
+    let client = setup_client(config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    let (tx, nft_mint) = match build_mint_transaction(
+        &program,
+        candy_machine_id,
+        candy_machine_state,
+        collection_update_authority,
+        address.0,
+        priority_fee,
+    ) {
+        Ok(transaction) => transaction,
+        Err(err) => {
+            // nothing was sent, so the mint can be retried
+            let mut results = results.lock().unwrap();
+            results.entry(address).or_default().push(TransactionResult {
+                signature: err.to_string(),
+                status: false,
+                mint: None,
+                blockhash: None,
+                unconfirmed: false,
+                error: Some(err.to_string()),
+                recipient,
+            });
+            return Err(err);
+        }
+    };
+
+    let signature = tx.signatures[0];
+
+    let index = {
+        let mut results = results.lock().unwrap();
+        append_pending_transaction(
+            results_path,
+            &PendingTransaction {
+                address,
+                signature: signature.to_string(),
+                mint: nft_mint.to_string(),
+                blockhash: tx.message.recent_blockhash.to_string(),
+                recipient: recipient.clone(),
+            },
+        )?;
+
+        // assume the transaction succeeds until we know otherwise, to avoid
+        // sending twice to a recipient
+        let transactions = results.entry(address).or_default();
+        transactions.push(TransactionResult {
+            signature: signature.to_string(),
+            status: true,
+            mint: Some(nft_mint.to_string()),
+            blockhash: Some(tx.message.recent_blockhash.to_string()),
+            unconfirmed: true,
+            error: None,
+            recipient,
+        });
+        transactions.len() - 1
+    };
+
+    let res = send_mint_transaction(&program, &tx, &nft_mint);
+
+    let mut results = results.lock().unwrap();
+    let transaction = &mut results.get_mut(&address).unwrap()[index];
+
+    match &res {
+        Ok(signature) => {
+            transaction.unconfirmed = false;
+            record_mint(
+                registry,
+                &MintRecord::new(
+                    &candy_machine_id,
+                    &nft_mint,
+                    &address.0,
+                    signature,
+                    "airdrop",
+                ),
+            );
+        }
+        Err(err) => {
+            transaction.error = Some(err.to_string());
+
+            // only failed transactions are known to not have minted; other
+            // errors (e.g., timeouts) are left to be reconciled
+            if is_failed_transaction(err) {
+                transaction.status = false;
+                transaction.unconfirmed = false;
+            }
+        }
+    }
+
+    res
+}
+
+/// Returns whether the error indicates that the transaction failed (as opposed
+/// to an unknown outcome). Other errors (e.g., the metadata account not found
+/// after the transaction was confirmed) are left to be reconciled.
+fn is_failed_transaction(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ClientError>()
+        .and_then(|err| err.get_transaction_error())
+        .is_some()
+}
+
+#[test]
+fn test_is_failed_transaction() {
+    use anchor_client::solana_sdk::transaction::TransactionError;
+
+    let failed = ClientError::from(TransactionError::InsufficientFundsForFee);
+    assert!(is_failed_transaction(&failed.into()));
+
+    let timeout = ClientError::from(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "timed out",
+    ));
+    assert!(!is_failed_transaction(&timeout.into()));
+
+    assert!(!is_failed_transaction(&anyhow!(
+        "Minting most likely failed with a bot tax"
+    )));
+}
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+use anyhow::Result;
+use console::style;
+use solana_client::rpc_client::RpcClient;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    airdrop::{
+        structs::{AirDropResults, SerdePubkey},
+        utils::{
+            clear_pending_transactions, merge_pending_transactions, read_airdrop_results,
+            write_airdrop_results,
+        },
+    },
+    candy_machine::CANDY_MACHINE_ID,
+    common::*,
+    pdas::find_metadata_pda,
+    utils::*,
+};
+
+/// Maximum number of signatures per status request.
+const MAX_SIGNATURES_PER_REQUEST: usize = 256;
+
+pub struct AirdropReconcileArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub results: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// The NFT was minted to the recipient.
+    Minted,
+    /// The transaction failed or expired without minting.
+    Failed(String),
+    /// The transaction can still land.
+    Pending,
+}
+
+pub fn process_airdrop_reconcile(args: AirdropReconcileArgs) -> Result<()> {
+    println!(
+        "{} {}Loading airdrop results",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let mut results = read_airdrop_results(&args.results)?;
+    let recorded = merge_pending_transactions(&mut results, &args.results)?;
+
+    // (address, index) of the transactions to reconcile
+    let mut unconfirmed: Vec<(SerdePubkey, usize)> = Vec::new();
+
+    for (address, transactions) in &results {
+        for (index, transaction) in transactions.iter().enumerate() {
+            if transaction.unconfirmed {
+                unconfirmed.push((*address, index));
+            }
+        }
+    }
+
+    println!(
+        "{} {} ({} recorded before being sent)",
+        style("Unconfirmed transactions:").bold(),
+        unconfirmed.len(),
+        recorded
+    );
+
+    println!(
+        "\n{} {}Checking transactions",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    if unconfirmed.is_empty() {
+        write_airdrop_results(&results, &args.results)?;
+        clear_pending_transactions(&args.results)?;
+
+        println!(
+            "\n{}{}",
+            PAPER_EMOJI,
+            style("All transactions are reconciled.").dim()
+        );
+        return Ok(());
+    }
+
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+    let rpc_client = program.rpc();
+
+    let pb = progress_bar_with_style(unconfirmed.len() as u64);
+    let mut outcomes = Vec::with_capacity(unconfirmed.len());
+
+    for chunk in unconfirmed.chunks(MAX_SIGNATURES_PER_REQUEST) {
+        let chunk_outcomes = reconcile_transactions(&rpc_client, &results, chunk)?;
+        pb.inc(chunk.len() as u64);
+        outcomes.extend(chunk.iter().copied().zip(chunk_outcomes));
+    }
+
+    pb.finish_and_clear();
+
+    let (mut minted, mut failed, mut pending) = (0, 0, 0);
+
+    for ((address, index), outcome) in outcomes {
+        let transaction = &mut results.get_mut(&address).unwrap()[index];
+
+        match outcome {
+            Outcome::Minted => {
+                minted += 1;
+                transaction.status = true;
+                transaction.unconfirmed = false;
+                transaction.error = None;
+            }
+            Outcome::Failed(error) => {
+                failed += 1;
+                // failed transactions are minted again on the next airdrop run
+                transaction.status = false;
+                transaction.unconfirmed = false;
+                transaction.error = Some(error);
+            }
+            Outcome::Pending => pending += 1,
+        }
+    }
+
+    write_airdrop_results(&results, &args.results)?;
+    // all recorded transactions are now part of the results file
+    clear_pending_transactions(&args.results)?;
+
+    println!("{} {}", style("Minted:").bold(), minted);
+    println!("{} {}", style("Failed:").bold(), failed);
+
+    if pending > 0 {
+        println!(
+            "{} {} {}",
+            style("Pending:").bold(),
+            pending,
+            style("(blockhash still valid, re-run reconcile later)").dim()
+        );
+    }
+
+    if failed > 0 {
+        println!(
+            "\n{}{}",
+            WARNING_EMOJI,
+            style("Re-run the airdrop to mint the failed items.").yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn reconcile_transactions(
+    rpc_client: &RpcClient,
+    results: &AirDropResults,
+    transactions: &[(SerdePubkey, usize)],
+) -> Result<Vec<Outcome>> {
+    let mut outcomes = Vec::with_capacity(transactions.len());
+    let mut signatures = Vec::with_capacity(transactions.len());
+
+    for (address, index) in transactions {
+        let transaction = &results[address][*index];
+        signatures.push(Signature::from_str(&transaction.signature).map_err(|_| {
+            anyhow!(
+                "Invalid signature '{}' for recipient {}",
+                transaction.signature,
+                address
+            )
+        })?);
+    }
+
+    let statuses = rpc_client
+        .get_signature_statuses_with_history(&signatures)?
+        .value;
+
+    for ((address, index), status) in transactions.iter().zip(statuses) {
+        let transaction = &results[address][*index];
+
+        let outcome = match status {
+            Some(status) => match status.err {
+                // a successful transaction does not mint the NFT when the bot
+                // tax is charged, in which case the metadata does not exist
+                None => match &transaction.mint {
+                    Some(mint) if !metadata_exists(rpc_client, &Pubkey::from_str(mint)?)? => {
+                        Outcome::Failed("Transaction did not mint the NFT (bot tax)".to_string())
+                    }
+                    _ => Outcome::Minted,
+                },
+                Some(err) => Outcome::Failed(format!("Transaction failed: {err}")),
+            },
+            None => {
+                // the signature is unknown: checks whether the recipient holds
+                // the NFT, otherwise whether the transaction can still land
+                let minted = match &transaction.mint {
+                    Some(mint) => holds_token(rpc_client, &address.0, &Pubkey::from_str(mint)?),
+                    None => false,
+                };
+
+                let expired = match &transaction.blockhash {
+                    Some(blockhash) => !rpc_client.is_blockhash_valid(
+                        &Hash::from_str(blockhash)?,
+                        CommitmentConfig::processed(),
+                    )?,
+                    None => true,
+                };
+
+                if minted {
+                    Outcome::Minted
+                } else if expired {
+                    Outcome::Failed("Transaction not found and blockhash expired".to_string())
+                } else {
+                    Outcome::Pending
+                }
+            }
+        };
+
+        outcomes.push(outcome);
+    }
+
+    Ok(outcomes)
+}
+
+/// Returns whether the metadata account of the mint exists.
+fn metadata_exists(rpc_client: &RpcClient, mint: &Pubkey) -> Result<bool> {
+    Ok(rpc_client
+        .get_account_with_commitment(&find_metadata_pda(mint), CommitmentConfig::confirmed())?
+        .value
+        .is_some())
+}
+
+/// Returns whether the owner holds the token in its associated token account.
+fn holds_token(rpc_client: &RpcClient, owner: &Pubkey, mint: &Pubkey) -> bool {
+    let token_account = get_associated_token_address(owner, mint);
+
+    rpc_client
+        .get_token_account_balance(&token_account)
+        .map(|balance| balance.amount == "1")
+        .unwrap_or(false)
+}
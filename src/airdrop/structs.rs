@@ -62,10 +62,59 @@ impl Serialize for SerdePubkey {
 
 pub type AirDropTargets = HashMap<SerdePubkey, u64>;
 
+/// Metadata of the recipients of an airdrop list, by address.
+pub type AirDropRecipientInfos = HashMap<SerdePubkey, RecipientInfo>;
+
+/// Optional metadata of a recipient, carried into the airdrop results.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RecipientInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// Recipient of an airdrop list, with optional metadata.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AirDropRecipient {
+    pub address: SerdePubkey,
+    pub count: u64,
+    #[serde(flatten)]
+    pub info: RecipientInfo,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransactionResult {
     pub signature: String,
     pub status: bool,
+    /// Address of the NFT mint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    /// Blockhash of the transaction, used to check whether it can still land.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<String>,
+    /// The outcome of the transaction is unknown (e.g., RPC timeout) and needs
+    /// to be reconciled.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unconfirmed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Metadata of the recipient from the airdrop list.
+    #[serde(flatten)]
+    pub recipient: RecipientInfo,
+}
+
+/// Transaction recorded before it is sent.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PendingTransaction {
+    pub address: SerdePubkey,
+    pub signature: String,
+    pub mint: String,
+    pub blockhash: String,
+    #[serde(flatten)]
+    pub recipient: RecipientInfo,
 }
 
 pub type AirDropResults = HashMap<SerdePubkey, Vec<TransactionResult>>;
@@ -0,0 +1,168 @@
+use std::{fs, path::Path, str::FromStr};
+
+use anchor_client::solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, pubkey::Pubkey, signature::Signature,
+};
+use anyhow::Result;
+use console::style;
+use mpl_candy_guard::{
+    accounts::{Unwrap as UnwrapAccount, Wrap as WrapAccount},
+    instruction::{Unwrap, Wrap},
+    state::CandyGuard,
+};
+use mpl_candy_machine_core::CandyMachine;
+use serde::{Deserialize, Serialize};
+
+use crate::{airdrop::errors::AirDropError, candy_machine::*, common::*, config::SugarConfig};
+
+/// Candy guard temporarily removed from a candy machine, recorded so it can be
+/// added back if sugar stops before doing so.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnwrappedCandyGuard {
+    pub candy_guard: String,
+    pub candy_machine: String,
+}
+
+/// Path of the file recording an unwrapped candy guard.
+pub fn unwrapped_candy_guard_path(results_path: &str) -> String {
+    format!("{results_path}.unwrapped")
+}
+
+/// Returns the candy guard that is the mint authority of the candy machine, if
+/// any, checking that the payer can unwrap it.
+pub fn get_wrapping_candy_guard(
+    config: &SugarConfig,
+    candy_machine_state: &CandyMachine,
+) -> Result<Option<Pubkey>> {
+    let client = setup_client(config)?;
+    let program = client.program(mpl_candy_guard::ID);
+    let mint_authority = candy_machine_state.mint_authority;
+
+    let account = program
+        .rpc()
+        .get_account_with_commitment(&mint_authority, CommitmentConfig::confirmed())?
+        .value;
+
+    match account {
+        Some(account) if account.owner == mpl_candy_guard::ID => {
+            let candy_guard: CandyGuard = program.account(mint_authority)?;
+            let payer = config.keypair.pubkey();
+
+            if candy_guard.authority != payer || candy_machine_state.authority != payer {
+                return Err(
+                    AirDropError::InvalidCandyGuardAuthority(mint_authority.to_string()).into(),
+                );
+            }
+
+            Ok(Some(mint_authority))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Removes the candy guard as the mint authority of the candy machine. The
+/// candy guard is recorded before being removed, so it can always be added
+/// back by [`rewrap_candy_guard`].
+pub fn unwrap_candy_guard(
+    config: &SugarConfig,
+    candy_guard: Pubkey,
+    candy_machine: Pubkey,
+    results_path: &str,
+    priority_fee: u64,
+) -> Result<Signature> {
+    let unwrapped = UnwrappedCandyGuard {
+        candy_guard: candy_guard.to_string(),
+        candy_machine: candy_machine.to_string(),
+    };
+    fs::write(
+        unwrapped_candy_guard_path(results_path),
+        serde_json::to_string(&unwrapped)?,
+    )?;
+
+    let client = setup_client(config)?;
+    let program = client.program(mpl_candy_guard::ID);
+    let payer = config.keypair.pubkey();
+
+    let tx = program
+        .request()
+        .instruction(ComputeBudgetInstruction::set_compute_unit_price(
+            priority_fee,
+        ))
+        .accounts(UnwrapAccount {
+            candy_guard,
+            authority: payer,
+            candy_machine,
+            candy_machine_authority: payer,
+            candy_machine_program: CANDY_MACHINE_ID,
+        })
+        .args(Unwrap {});
+
+    Ok(tx.send()?)
+}
+
+/// Adds back the candy guard recorded as unwrapped, if any. Returns the
+/// candy guard id when it was wrapped.
+pub fn rewrap_candy_guard(
+    config: &SugarConfig,
+    results_path: &str,
+    priority_fee: u64,
+) -> Result<Option<Pubkey>> {
+    let path = unwrapped_candy_guard_path(results_path);
+
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+
+    let unwrapped: UnwrappedCandyGuard = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    let candy_guard = Pubkey::from_str(&unwrapped.candy_guard)?;
+    let candy_machine = Pubkey::from_str(&unwrapped.candy_machine)?;
+
+    // the unwrap might not have happened if sugar stopped right after
+    // recording it
+    let candy_machine_state = get_candy_machine_state(config, &candy_machine)?;
+
+    if candy_machine_state.mint_authority != candy_guard {
+        let client = setup_client(config)?;
+        let program = client.program(mpl_candy_guard::ID);
+        let payer = config.keypair.pubkey();
+
+        let tx = program
+            .request()
+            .instruction(ComputeBudgetInstruction::set_compute_unit_price(
+                priority_fee,
+            ))
+            .accounts(WrapAccount {
+                candy_guard,
+                authority: payer,
+                candy_machine,
+                candy_machine_program: CANDY_MACHINE_ID,
+                candy_machine_authority: payer,
+            })
+            .args(Wrap {});
+
+        tx.send()?;
+    }
+
+    fs::remove_file(path)?;
+
+    Ok(Some(candy_guard))
+}
+
+/// Prints the outcome of adding back the candy guard.
+pub fn print_rewrap_result(result: &Result<Option<Pubkey>>) {
+    match result {
+        Ok(Some(candy_guard)) => println!(
+            "\n{}{} {}",
+            WRAP_EMOJI,
+            style("Candy guard added back:").bold(),
+            candy_guard
+        ),
+        Ok(None) => (),
+        Err(err) => println!(
+            "\n{}{} {}\n  -> re-run the airdrop to add the candy guard back",
+            ERROR_EMOJI,
+            style("Failed to add the candy guard back:").red().bold(),
+            err
+        ),
+    }
+}
@@ -1,43 +1,50 @@
-use std::path::Path;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
 
 use anyhow::Result;
+use console::style;
+use serde::Deserialize;
 
 use crate::{
     airdrop::{
         errors::AirDropError,
-        structs::{AirDropResults, AirDropTargets},
+        structs::{
+            AirDropRecipient, AirDropRecipientInfos, AirDropResults, AirDropTargets,
+            PendingTransaction, RecipientInfo, SerdePubkey, TransactionResult,
+        },
     },
     common::*,
 };
 
-pub fn write_airdrop_results(airdrop_results: &AirDropResults) -> Result<()> {
-    let airdrop_results_path = Path::new("airdrop_results.json");
+pub fn write_airdrop_results(airdrop_results: &AirDropResults, path: &str) -> Result<()> {
+    let airdrop_results_path = Path::new(path);
     let f = File::create(airdrop_results_path)?;
     serde_json::to_writer_pretty(f, airdrop_results)?;
     Ok(())
 }
 
-pub fn load_airdrop_results(airdrop_list: &mut AirDropTargets) -> Result<AirDropResults> {
+pub fn load_airdrop_results(
+    airdrop_list: &mut AirDropTargets,
+    path: &str,
+) -> Result<AirDropResults> {
     // Will load previous airdrop results from file and will also sync the results with the targets
-    let airdrop_result_path_name = "airdrop_results.json";
-    let airdrop_results_path = Path::new(airdrop_result_path_name);
-    if !airdrop_results_path.exists() {
-        return Ok(AirDropResults::new());
-    }
+    let mut results = read_airdrop_results(path)?;
 
-    let file = File::open(airdrop_results_path).map_err(|err| {
-        AirDropError::FailedToOpenAirDropResultsFile(
-            airdrop_result_path_name.to_string(),
-            err.to_string(),
-        )
-    })?;
+    // transactions sent by an interrupted run are considered as sent until
+    // they are reconciled, to avoid sending twice to a recipient
+    let pending = merge_pending_transactions(&mut results, path)?;
 
-    let results: AirDropResults = serde_json::from_reader(file).map_err(|err| {
-        AirDropError::AirDropResultsFileWrongFormat(
-            airdrop_result_path_name.to_string(),
-            err.to_string(),
-        )
-    })?;
+    if pending > 0 {
+        println!(
+            "{}{} {} transaction(s) of a previous run need to be reconciled (sugar airdrop reconcile)",
+            WARNING_EMOJI,
+            style("warning:").yellow().bold(),
+            pending
+        );
+    }
 
     for (address, transactions) in results.iter() {
         if !airdrop_list.contains_key(address) {
@@ -60,22 +67,441 @@ pub fn load_airdrop_results(airdrop_list: &mut AirDropTargets) -> Result<AirDrop
     Ok(results)
 }
 
-pub fn load_airdrop_list(airdrop_list: String) -> Result<AirDropTargets> {
-    let airdrop_list_path = Path::new(&airdrop_list);
+/// Reads the airdrop results file, returning empty results if the file does
+/// not exist.
+pub fn read_airdrop_results(path: &str) -> Result<AirDropResults> {
+    let airdrop_results_path = Path::new(path);
+    if !airdrop_results_path.exists() {
+        return Ok(AirDropResults::new());
+    }
+
+    let file = File::open(airdrop_results_path).map_err(|err| {
+        AirDropError::FailedToOpenAirDropResultsFile(path.to_string(), err.to_string())
+    })?;
+
+    let results: AirDropResults = serde_json::from_reader(file).map_err(|err| {
+        AirDropError::AirDropResultsFileWrongFormat(path.to_string(), err.to_string())
+    })?;
+
+    Ok(results)
+}
+
+/// Path of the journal where transactions are recorded before being sent.
+pub fn pending_transactions_path(results_path: &str) -> String {
+    format!("{results_path}.pending")
+}
+
+/// Records a transaction before it is sent, so its outcome can be reconciled
+/// even if the airdrop is interrupted.
+pub fn append_pending_transaction(results_path: &str, pending: &PendingTransaction) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(pending_transactions_path(results_path))?;
+    writeln!(file, "{}", serde_json::to_string(pending)?)?;
+    Ok(())
+}
+
+/// Adds the recorded transactions missing from the results as unconfirmed,
+/// returning the number of transactions added.
+pub fn merge_pending_transactions(
+    results: &mut AirDropResults,
+    results_path: &str,
+) -> Result<usize> {
+    let pending_path = pending_transactions_path(results_path);
+
+    let content = match fs::read_to_string(&pending_path) {
+        Ok(content) => content,
+        Err(_) => return Ok(0),
+    };
+
+    let mut added = 0;
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let pending: PendingTransaction = match serde_json::from_str(line) {
+            Ok(pending) => pending,
+            // the last line might be incomplete if sugar was interrupted while writing it
+            Err(_) if index == content.lines().count() - 1 => continue,
+            Err(err) => {
+                return Err(AirDropError::AirDropResultsFileWrongFormat(
+                    pending_path,
+                    err.to_string(),
+                )
+                .into())
+            }
+        };
+
+        let transactions = results.entry(pending.address).or_default();
+
+        if !transactions
+            .iter()
+            .any(|transaction| transaction.signature == pending.signature)
+        {
+            transactions.push(TransactionResult {
+                signature: pending.signature,
+                status: true,
+                mint: Some(pending.mint),
+                blockhash: Some(pending.blockhash),
+                unconfirmed: true,
+                error: None,
+                recipient: pending.recipient,
+            });
+            added += 1;
+        }
+    }
+
+    Ok(added)
+}
+
+/// Removes the journal of recorded transactions, once they are all part of the
+/// results file.
+pub fn clear_pending_transactions(results_path: &str) -> Result<()> {
+    let pending_path = pending_transactions_path(results_path);
+
+    if Path::new(&pending_path).exists() {
+        fs::remove_file(pending_path)?;
+    }
+
+    Ok(())
+}
+
+/// Loads the airdrop list, returning the number of NFTs and the metadata of
+/// each recipient.
+pub fn load_airdrop_list(airdrop_list: String) -> Result<(AirDropTargets, AirDropRecipientInfos)> {
+    let mut targets = AirDropTargets::new();
+    let mut infos = AirDropRecipientInfos::new();
+
+    for recipient in load_airdrop_recipients(&airdrop_list)? {
+        targets.insert(recipient.address, recipient.count);
+        infos.insert(recipient.address, recipient.info.clone());
+    }
+
+    Ok((targets, infos))
+}
+
+/// Loads the recipients of an airdrop list. The format is determined by the
+/// file extension: CSV (.csv), JSON lines (.jsonl or .ndjson) or JSON, either a
+/// map of address to number of tokens or an array of recipients. Duplicated
+/// addresses are merged.
+pub fn load_airdrop_recipients(airdrop_list: &str) -> Result<Vec<AirDropRecipient>> {
+    let airdrop_list_path = Path::new(airdrop_list);
     if !airdrop_list_path.exists() {
-        return Err(AirDropError::AirDropListFileNotFound(airdrop_list).into());
+        return Err(AirDropError::AirDropListFileNotFound(airdrop_list.to_string()).into());
     }
 
-    let file = File::open(airdrop_list_path).map_err(|err| {
-        AirDropError::FailedToOpenAirDropListFile(airdrop_list.clone(), err.to_string())
+    let content = fs::read_to_string(airdrop_list_path).map_err(|err| {
+        AirDropError::FailedToOpenAirDropListFile(airdrop_list.to_string(), err.to_string())
     })?;
 
-    let targets: AirDropTargets = match serde_json::from_reader(file).map_err(|err| {
-        AirDropError::AirDropListFileWrongFormat(airdrop_list.clone(), err.to_string())
-    }) {
-        Ok(targets) => targets,
-        Err(err) => return Err(err.into()),
+    let extension = airdrop_list_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let recipients = match extension.as_deref() {
+        Some("csv") => parse_csv_recipients(airdrop_list, &content)?,
+        Some("jsonl") | Some("ndjson") => parse_json_lines_recipients(airdrop_list, &content)?,
+        _ => {
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum JsonList {
+                Targets(HashMap<SerdePubkey, u64>),
+                Recipients(Vec<AirDropRecipient>),
+            }
+
+            let list: JsonList = serde_json::from_str(&content).map_err(|err| {
+                AirDropError::AirDropListFileWrongFormat(airdrop_list.to_string(), err.to_string())
+            })?;
+
+            match list {
+                JsonList::Targets(targets) => targets
+                    .into_iter()
+                    .map(|(address, count)| AirDropRecipient {
+                        address,
+                        count,
+                        info: RecipientInfo::default(),
+                    })
+                    .collect(),
+                JsonList::Recipients(recipients) => recipients,
+            }
+        }
     };
 
-    Ok(targets)
+    let (recipients, duplicates) = merge_recipients(recipients);
+
+    for (address, occurrences) in duplicates {
+        println!(
+            "{}{} {} appears {} times in the airdrop list, merging counts",
+            WARNING_EMOJI,
+            style("warning:").yellow().bold(),
+            address,
+            occurrences
+        );
+    }
+
+    Ok(recipients)
+}
+
+/// Merges recipients with the same address, adding up their counts. Returns the
+/// merged recipients (in order of first appearance) and the duplicated
+/// addresses with their number of occurrences.
+fn merge_recipients(
+    recipients: Vec<AirDropRecipient>,
+) -> (Vec<AirDropRecipient>, Vec<(SerdePubkey, usize)>) {
+    let mut merged: Vec<AirDropRecipient> = Vec::new();
+    let mut positions: HashMap<SerdePubkey, usize> = HashMap::new();
+    let mut occurrences: HashMap<SerdePubkey, usize> = HashMap::new();
+
+    for recipient in recipients {
+        *occurrences.entry(recipient.address).or_insert(0) += 1;
+
+        match positions.get(&recipient.address) {
+            Some(position) => {
+                let existing = &mut merged[*position];
+                existing.count += recipient.count;
+                // keeps the metadata of the first occurrence
+                let info = &mut existing.info;
+                info.label = info.label.take().or(recipient.info.label);
+                info.group = info.group.take().or(recipient.info.group);
+                info.notes = info.notes.take().or(recipient.info.notes);
+            }
+            None => {
+                positions.insert(recipient.address, merged.len());
+                merged.push(recipient);
+            }
+        }
+    }
+
+    let duplicates = merged
+        .iter()
+        .filter_map(|recipient| {
+            let count = occurrences[&recipient.address];
+            (count > 1).then_some((recipient.address, count))
+        })
+        .collect();
+
+    (merged, duplicates)
+}
+
+fn parse_json_lines_recipients(path: &str, content: &str) -> Result<Vec<AirDropRecipient>> {
+    let mut recipients = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let recipient: AirDropRecipient = serde_json::from_str(line).map_err(|err| {
+            AirDropError::AirDropListInvalidLine(path.to_string(), index + 1, err.to_string())
+        })?;
+        recipients.push(recipient);
+    }
+
+    Ok(recipients)
+}
+
+fn parse_csv_recipients(path: &str, content: &str) -> Result<Vec<AirDropRecipient>> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header: Vec<String> = match lines.next() {
+        Some((index, line)) => split_csv_line(line)
+            .map_err(|message| {
+                AirDropError::AirDropListInvalidLine(path.to_string(), index + 1, message)
+            })?
+            .into_iter()
+            .map(|column| column.to_lowercase())
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+
+    let column = |name: &str| header.iter().position(|column| column == name);
+
+    let address_column = column("address").ok_or_else(|| {
+        AirDropError::AirDropListFileWrongFormat(
+            path.to_string(),
+            "missing 'address' column".to_string(),
+        )
+    })?;
+    let count_column = column("count");
+    let label_column = column("label");
+    let group_column = column("group");
+    let notes_column = column("notes");
+
+    let mut recipients = Vec::new();
+
+    for (index, line) in lines {
+        let invalid_line = |message: String| {
+            AirDropError::AirDropListInvalidLine(path.to_string(), index + 1, message)
+        };
+
+        let values = split_csv_line(line).map_err(invalid_line)?;
+        let value = |column: Option<usize>| {
+            column
+                .and_then(|column| values.get(column))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+
+        let address = value(Some(address_column))
+            .ok_or_else(|| invalid_line("missing address".to_string()))?;
+        let address = SerdePubkey::from_str(&address).map_err(invalid_line)?;
+
+        // the count defaults to one token per row
+        let count = match value(count_column) {
+            Some(count) => count
+                .parse::<u64>()
+                .map_err(|err| invalid_line(format!("invalid count '{count}': {err}")))?,
+            None => 1,
+        };
+
+        recipients.push(AirDropRecipient {
+            address,
+            count,
+            info: RecipientInfo {
+                label: value(label_column),
+                group: value(group_column),
+                notes: value(notes_column),
+            },
+        });
+    }
+
+    Ok(recipients)
+}
+
+/// Splits a CSV line into its (trimmed) values. Values can be quoted to include
+/// commas, with quotes escaped as `""`; quoted values cannot span lines.
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            // quotes are only special at the start of a value
+            '"' if current.trim().is_empty() => {
+                current.clear();
+                quoted = true;
+            }
+            ',' if !quoted => {
+                values.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted value (values cannot span lines)".to_string());
+    }
+
+    values.push(current.trim().to_string());
+    Ok(values)
+}
+
+#[test]
+fn test_parse_csv_recipients() {
+    let content = "address,count,label,notes\n\
+        11111111111111111111111111111111,2,team,\"first, second\"\n\
+        \n\
+        SysvarRent111111111111111111111111111111111,,,\n\
+        11111111111111111111111111111111,1,,";
+
+    let recipients = parse_csv_recipients("list.csv", content).unwrap();
+    assert_eq!(recipients.len(), 3);
+    assert_eq!(recipients[0].info.notes.as_deref(), Some("first, second"));
+    assert_eq!(recipients[1].count, 1);
+
+    let (merged, duplicates) = merge_recipients(recipients);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].count, 3);
+    assert_eq!(merged[0].info.label.as_deref(), Some("team"));
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].1, 2);
+
+    assert!(parse_csv_recipients("list.csv", "address\nnot-an-address").is_err());
+
+    // quotes are escaped as "" and quoted values cannot span lines
+    assert_eq!(
+        split_csv_line(r#"a,"say ""hi""", b"c"#).unwrap(),
+        vec!["a", r#"say "hi""#, r#"b"c"#]
+    );
+    let err = parse_csv_recipients(
+        "list.csv",
+        "address,notes\n11111111111111111111111111111111,\"first\nsecond\"",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
+
+#[test]
+fn test_merge_pending_transactions() {
+    let results_path = std::env::temp_dir()
+        .join(format!("airdrop_results_{}.json", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    let address = SerdePubkey::from_str("11111111111111111111111111111111").unwrap();
+
+    for signature in ["sig1", "sig2"] {
+        append_pending_transaction(
+            &results_path,
+            &PendingTransaction {
+                address,
+                signature: signature.to_string(),
+                mint: "mint".to_string(),
+                blockhash: "blockhash".to_string(),
+                recipient: RecipientInfo {
+                    label: Some("team".to_string()),
+                    ..Default::default()
+                },
+            },
+        )
+        .unwrap();
+    }
+    // simulates an interrupted write
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(pending_transactions_path(&results_path))
+        .unwrap();
+    write!(file, "{{\"address\":").unwrap();
+
+    let mut results = AirDropResults::new();
+    results.insert(
+        address,
+        vec![TransactionResult {
+            signature: "sig1".to_string(),
+            status: true,
+            mint: None,
+            blockhash: None,
+            unconfirmed: false,
+            error: None,
+            recipient: Default::default(),
+        }],
+    );
+
+    assert_eq!(
+        merge_pending_transactions(&mut results, &results_path).unwrap(),
+        1
+    );
+    assert_eq!(results[&address].len(), 2);
+    assert!(results[&address][1].unconfirmed);
+    assert_eq!(
+        results[&address][1].recipient.label.as_deref(),
+        Some("team")
+    );
+
+    clear_pending_transactions(&results_path).unwrap();
+    assert!(!Path::new(&pending_transactions_path(&results_path)).exists());
 }
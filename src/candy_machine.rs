@@ -1,10 +1,15 @@
 use anchor_client::{solana_sdk::pubkey::Pubkey, ClientError};
-use anchor_lang::AnchorDeserialize;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
 use anyhow::{anyhow, Result};
 pub use mpl_candy_machine_core::ID as CANDY_MACHINE_ID;
-use mpl_candy_machine_core::{CandyMachine, CandyMachineData};
+use mpl_candy_machine_core::{
+    constants::HIDDEN_SECTION, CandyMachine, CandyMachineData, ConfigLine,
+};
 
-use crate::{config::data::SugarConfig, pdas::get_metadata_pda, setup::setup_client};
+use crate::{
+    config::data::SugarConfig, constants::STRING_LEN_SIZE, pdas::get_metadata_pda,
+    setup::setup_client,
+};
 
 // To test a custom candy machine program, comment the mpl_candy_machine::ID line
 // above and use the following lines to declare the id to use:
@@ -60,3 +65,115 @@ pub fn load_candy_machine(
 
     Ok((candy_machine, rule_set))
 }
+
+/// Loads the candy machine and the config lines written on its account.
+pub fn load_candy_machine_config_lines(
+    sugar_config: &SugarConfig,
+    candy_machine_id: &Pubkey,
+) -> Result<(CandyMachine, Vec<Option<ConfigLine>>)> {
+    let client = setup_client(sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    let data = program.rpc().get_account_data(candy_machine_id)?;
+    let candy_machine = CandyMachine::try_deserialize(&mut data.as_slice())?;
+    let config_lines = get_config_lines(&candy_machine, &data)?;
+
+    Ok((candy_machine, config_lines))
+}
+
+/// Decodes the config lines of the candy machine account data, with the name
+/// and uri prefixes applied. Config lines not written yet are `None`.
+pub fn get_config_lines(
+    candy_machine: &CandyMachine,
+    data: &[u8],
+) -> Result<Vec<Option<ConfigLine>>> {
+    let settings = match &candy_machine.data.config_line_settings {
+        Some(settings) if candy_machine.data.hidden_settings.is_none() => settings,
+        // there are no config lines in a candy machine with hidden settings
+        _ => return Ok(Vec::new()),
+    };
+
+    let items_available = candy_machine.data.items_available as usize;
+    let name_length = settings.name_length as usize;
+    let uri_length = settings.uri_length as usize;
+    let line_size = name_length + uri_length;
+    let bit_mask_start = HIDDEN_SECTION + STRING_LEN_SIZE + items_available * line_size;
+
+    if data.len() < bit_mask_start + items_available / 8 + 1 {
+        return Err(anyhow!("Invalid candy machine account data length"));
+    }
+
+    let decode = |start: usize, length: usize, index: usize| {
+        String::from_utf8(data[start..start + length].to_vec())
+            .map(|value| value.trim_matches(char::from(0)).to_string())
+            .map_err(|_| anyhow!("Failed to decode config line {index}"))
+    };
+
+    let mut config_lines = Vec::with_capacity(items_available);
+
+    for index in 0..items_available {
+        let mask = 1u8 << (7 - index % 8);
+
+        if data[bit_mask_start + index / 8] & mask == 0 {
+            config_lines.push(None);
+            continue;
+        }
+
+        let name_start = HIDDEN_SECTION + STRING_LEN_SIZE + line_size * index;
+        let uri_start = name_start + name_length;
+
+        config_lines.push(Some(ConfigLine {
+            name: settings.prefix_name.clone() + &decode(name_start, name_length, index)?,
+            uri: settings.prefix_uri.clone() + &decode(uri_start, uri_length, index)?,
+        }));
+    }
+
+    Ok(config_lines)
+}
+
+#[test]
+fn test_get_config_lines() {
+    use mpl_candy_machine_core::ConfigLineSettings;
+
+    let candy_machine = CandyMachine {
+        data: CandyMachineData {
+            items_available: 10,
+            config_line_settings: Some(ConfigLineSettings {
+                prefix_name: "Sugar #".to_string(),
+                name_length: 2,
+                prefix_uri: "https://arweave.net/".to_string(),
+                uri_length: 4,
+                is_sequential: false,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let line_size = 6;
+    let bit_mask_start = HIDDEN_SECTION + STRING_LEN_SIZE + 10 * line_size;
+    let mut data = vec![0u8; bit_mask_start + 10 / 8 + 1];
+
+    // writes the config lines 0 and 9
+    for (index, name, uri) in [(0, "0", "abcd"), (9, "10", "wxyz")] {
+        let start = HIDDEN_SECTION + STRING_LEN_SIZE + index * line_size;
+        data[start..start + name.len()].copy_from_slice(name.as_bytes());
+        data[start + 2..start + 6].copy_from_slice(uri.as_bytes());
+        data[bit_mask_start + index / 8] |= 1 << (7 - index % 8);
+    }
+
+    let config_lines = get_config_lines(&candy_machine, &data).unwrap();
+
+    assert_eq!(config_lines.len(), 10);
+    assert_eq!(config_lines.iter().filter(|line| line.is_some()).count(), 2);
+
+    let first = config_lines[0].as_ref().unwrap();
+    assert_eq!(first.name, "Sugar #0");
+    assert_eq!(first.uri, "https://arweave.net/abcd");
+
+    let last = config_lines[9].as_ref().unwrap();
+    assert_eq!(last.name, "Sugar #10");
+    assert_eq!(last.uri, "https://arweave.net/wxyz");
+
+    assert!(get_config_lines(&candy_machine, &data[..bit_mask_start]).is_err());
+}
@@ -3,9 +3,13 @@ use clap::{Parser, Subcommand};
 use crate::{
     config::TokenStandard,
     constants::{
-        DEFAULT_AIRDROP_LIST, DEFAULT_AIRDROP_LIST_HELP, DEFAULT_ASSETS, DEFAULT_CACHE,
-        DEFAULT_CONFIG, DEFAULT_PRIORITY_FEE,
+        DEFAULT_AIRDROP_LIST, DEFAULT_AIRDROP_LIST_HELP, DEFAULT_AIRDROP_RESULTS, DEFAULT_ASSETS,
+        DEFAULT_CACHE, DEFAULT_CONFIG, DEFAULT_MINTS, DEFAULT_MINT_CONCURRENCY,
+        DEFAULT_MINT_RETRIES, DEFAULT_PRIORITY_FEE, DEFAULT_SNAPSHOT, DEFAULT_VERIFY_REPORT,
     },
+    mints::MintDiscovery,
+    output::OutputFormat,
+    tx::TxEncoding,
 };
 
 #[derive(Parser)]
@@ -15,6 +19,36 @@ pub struct Cli {
     #[clap(short, long, global = true)]
     pub log_level: Option<String>,
 
+    /// Export unsigned transactions to this file instead of sending them (supported by
+    /// 'config update', 'guard update', 'collection set', 'withdraw' and 'freeze unlock-funds')
+    #[clap(long, global = true)]
+    pub export_tx: Option<String>,
+
+    /// Authority of the exported transactions (e.g., a multisig), defaults to the keypair
+    #[clap(long, global = true, requires = "export-tx")]
+    pub export_authority: Option<String>,
+
+    /// Encoding of the exported transactions: base58 or base64
+    #[clap(long, global = true, default_value = "base58")]
+    pub export_encoding: TxEncoding,
+
+    /// Durable nonce account to use instead of a recent blockhash (supported by the
+    /// --export-tx commands and 'reveal'), create one with 'sugar tx create-nonce'. A nonce
+    /// account covers one pending transaction, so it cannot be used to export more than one
+    #[clap(long, global = true)]
+    pub nonce: Option<String>,
+
+    /// How the minted NFTs (and the candy machines for 'withdraw') are found: auto,
+    /// program-accounts, history (transaction history, cached in "mint_history_cache.json") or
+    /// registry (NFTs minted by sugar, recorded next to the cache file)
+    #[clap(long, global = true, default_value = "auto")]
+    pub mint_discovery: MintDiscovery,
+
+    /// Output format: text or json (prints the result as JSON to stdout and the progress to stderr,
+    /// only supported on unix)
+    #[clap(long, global = true, default_value = "text")]
+    pub output: OutputFormat,
+
     #[clap(subcommand)]
     pub command: Commands,
 }
@@ -23,7 +57,7 @@ pub struct Cli {
 pub enum Commands {
     /// Interact with the bundlr network
     Bundlr {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -53,7 +87,7 @@ pub enum Commands {
         #[clap(short, long, default_value = DEFAULT_CONFIG)]
         config: String,
 
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -72,6 +106,14 @@ pub enum Commands {
         /// The optional collection address where the candymachine will mint the tokens to
         #[clap(long)]
         collection_mint: Option<String>,
+
+        /// Pack more config lines per transaction by requesting a higher compute unit limit
+        #[clap(long)]
+        pack_config_lines: bool,
+
+        /// Print the estimated cost of the deploy without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
     },
 
     /// Manage freeze guard actions
@@ -107,7 +149,7 @@ pub enum Commands {
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
 
-        /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>) [default: solana config or "~/.config/solana/id.json"]
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -138,7 +180,7 @@ pub enum Commands {
 
     /// Mint one NFT from candy machine
     Mint {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -165,10 +207,22 @@ pub enum Commands {
         /// Address of candy machine to mint from.
         #[clap(long)]
         candy_machine: Option<String>,
+
+        /// Number of mints sent in parallel
+        #[clap(long, default_value_t = DEFAULT_MINT_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Number of retries of a mint on transient errors
+        #[clap(long, default_value_t = DEFAULT_MINT_RETRIES)]
+        retries: u32,
+
+        /// Path to a file recording the result of each mint; completed mints are skipped when re-run
+        #[clap(long)]
+        results: Option<String>,
     },
     /// Airdrop NFTs from candy machine
     Airdrop {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -191,11 +245,22 @@ pub enum Commands {
         /// List of airdrop targets.
         #[clap(long, default_value = DEFAULT_AIRDROP_LIST, help = DEFAULT_AIRDROP_LIST_HELP)]
         airdrop_list: String,
+
+        /// Path to the airdrop results file.
+        #[clap(long, default_value = DEFAULT_AIRDROP_RESULTS)]
+        results: String,
+
+        /// Temporarily remove the candy guard to airdrop, adding it back when finished or interrupted.
+        #[clap(long)]
+        unwrap: bool,
+
+        #[clap(subcommand)]
+        command: Option<AirdropCommand>,
     },
 
     /// Reveal the NFTs from a hidden settings candy machine
     Reveal {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -220,9 +285,63 @@ pub enum Commands {
         new_update_authority: Option<String>,
     },
 
+    /// Export the list of NFTs minted from a candy machine
+    Mints {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of candy machine.
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Path to the mint list file (.csv, .json or .jsonl).
+        #[clap(short = 'o', long, default_value = DEFAULT_MINTS)]
+        file: String,
+
+        /// RPC timeout to retrieve the mint list (in seconds).
+        #[clap(short, long)]
+        timeout: Option<u64>,
+    },
+
+    /// Export the current holders of the NFTs minted from a candy machine
+    Snapshot {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of candy machine.
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Path to the snapshot file (.csv, .json or .jsonl).
+        #[clap(short = 'o', long, default_value = DEFAULT_SNAPSHOT)]
+        file: String,
+
+        /// RPC timeout to retrieve the mint list (in seconds).
+        #[clap(short, long)]
+        timeout: Option<u64>,
+    },
+
     /// Show the on-chain config of an existing candy machine
     Show {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -244,7 +363,7 @@ pub enum Commands {
 
     /// Sign one or all NFTs from candy machine
     Sign {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -265,6 +384,12 @@ pub enum Commands {
         candy_machine_id: Option<String>,
     },
 
+    /// Submit transactions signed externally
+    Tx {
+        #[clap(subcommand)]
+        command: TxCommand,
+    },
+
     /// Upload assets to storage and creates the cache config
     Upload {
         /// Path to the directory with the assets to upload
@@ -275,7 +400,7 @@ pub enum Commands {
         #[clap(short, long, default_value = DEFAULT_CONFIG)]
         config: String,
 
-        /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>) [default: solana config or "~/.config/solana/id.json"]
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -292,12 +417,16 @@ pub enum Commands {
         cache: String,
     },
 
-    /// Validate JSON metadata files
+    /// Validate JSON metadata files and guards configuration
     Validate {
         /// Assets directory to upload, defaults to "assets"
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
 
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
         /// Strict mode: validate against JSON metadata standard exactly
         #[clap(long)]
         strict: bool,
@@ -309,7 +438,7 @@ pub enum Commands {
 
     /// Verify uploaded data
     Verify {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -320,6 +449,14 @@ pub enum Commands {
         /// Path to the cache file, defaults to "cache.json"
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
+
+        /// Fetch the metadata of each item and check its name and image link
+        #[clap(long)]
+        deep: bool,
+
+        /// Path to the report file of the metadata checks
+        #[clap(long, default_value = DEFAULT_VERIFY_REPORT)]
+        report: String,
     },
 
     /// Withdraw funds a from candy machine account closing it
@@ -328,7 +465,7 @@ pub enum Commands {
         #[clap(long)]
         candy_machine: Option<String>,
 
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -336,6 +473,11 @@ pub enum Commands {
         #[clap(short, long)]
         rpc_url: Option<String>,
 
+        /// Path to the cache file, defaults to "cache.json" (the mint history
+        /// cache is kept in its directory)
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
         /// Priority fee value
         #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
         priority_fee: u64,
@@ -372,7 +514,7 @@ pub enum ConfigSubcommands {
         #[clap(short, long)]
         rpc_url: Option<String>,
 
-        /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>) [default: solana config or "~/.config/solana/id.json"]
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -386,7 +528,7 @@ pub enum ConfigSubcommands {
         #[clap(short, long, default_value = DEFAULT_CONFIG)]
         config: String,
 
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -412,7 +554,7 @@ pub enum ConfigSubcommands {
     },
     /// Set specific candy machine config values
     Set {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -446,7 +588,7 @@ pub enum ConfigSubcommands {
 pub enum CollectionSubcommands {
     /// Set the collection mint on the candy machine
     Set {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -479,7 +621,7 @@ pub enum CollectionSubcommands {
 pub enum GuardCommand {
     /// Add a candy guard on a candy machine
     Add {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -507,9 +649,85 @@ pub enum GuardCommand {
         #[clap(long)]
         candy_guard: Option<String>,
     },
+    /// Check whether a wallet can mint from a candy guard (group)
+    Check {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the candy machine [defaults to cache value].
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Address of the candy guard [defaults to cache value].
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Address of the wallet to check.
+        #[clap(long)]
+        wallet: String,
+
+        /// Candy guard group label.
+        #[clap(long)]
+        group: Option<String>,
+
+        /// Date and time to check against [defaults to now].
+        #[clap(long)]
+        at: Option<String>,
+    },
+    /// Manage the guard groups of the config file
+    Group {
+        #[clap(subcommand)]
+        command: GuardGroupCommand,
+    },
+    /// Generate guard groups from a phases file
+    Phases {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Address of the candy guard.
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Update the candy guard on-chain after changing the config file.
+        #[clap(long)]
+        update: bool,
+
+        /// Replace existing guard groups without confirmation.
+        #[clap(long)]
+        force: bool,
+
+        /// Path to the phases file.
+        phases: String,
+    },
     /// Remove a candy guard from a candy machine
     Remove {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -533,9 +751,14 @@ pub enum GuardCommand {
         #[clap(long)]
         candy_guard: Option<String>,
     },
+    /// Route an instruction to a guard of a candy guard
+    Route {
+        #[clap(subcommand)]
+        command: GuardRouteCommand,
+    },
     /// Show the on-chain config of an existing candy guard
     Show {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -550,10 +773,18 @@ pub enum GuardCommand {
         /// Address of the candy guard.
         #[clap(long)]
         candy_guard: Option<String>,
+
+        /// Address of the candy machine (used to report guard usage) [defaults to cache value].
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Timezone to show dates in, in addition to UTC (e.g., "local" or "+02:00").
+        #[clap(long)]
+        timezone: Option<String>,
     },
     /// Update the configuration of a candy guard
     Update {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -577,9 +808,98 @@ pub enum GuardCommand {
         #[clap(long)]
         candy_guard: Option<String>,
     },
+    /// Show the live usage of the guards of a candy guard (mint counters, allocations, etc.)
+    Usage {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the candy machine [defaults to cache value].
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Address of the candy guard [defaults to cache value].
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Address of a wallet to report the mint limit counters.
+        #[clap(long)]
+        wallet: Option<String>,
+    },
     /// Withdraw funds from a candy guard account closing it
     Withdraw {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the candy guard.
+        #[clap(long)]
+        candy_guard: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GuardGroupCommand {
+    /// Add a new guard group
+    Add {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Address of the candy guard.
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Update the candy guard on-chain after changing the config file.
+        #[clap(long)]
+        update: bool,
+
+        /// Label of the new group.
+        label: String,
+
+        /// Label of an existing group to copy the guards from.
+        #[clap(long)]
+        from: Option<String>,
+    },
+    /// Remove a guard group
+    Remove {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -587,17 +907,155 @@ pub enum GuardCommand {
         #[clap(short, long)]
         rpc_url: Option<String>,
 
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
         /// Priority fee value
         #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
         priority_fee: u64,
 
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Address of the candy guard.
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Update the candy guard on-chain after changing the config file.
+        #[clap(long)]
+        update: bool,
+
+        /// Label of the group to remove.
+        label: String,
+    },
+    /// Rename a guard group
+    Rename {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
         /// Path to the cache file, defaults to "cache.json"
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
 
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
         /// Address of the candy guard.
         #[clap(long)]
         candy_guard: Option<String>,
+
+        /// Update the candy guard on-chain after changing the config file.
+        #[clap(long)]
+        update: bool,
+
+        /// Label of the group to rename.
+        label: String,
+
+        /// New label of the group.
+        new_label: String,
+    },
+    /// Set a guard value on a guard group (e.g., 'solPayment.value 1.5')
+    Set {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Address of the candy guard.
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Update the candy guard on-chain after changing the config file.
+        #[clap(long)]
+        update: bool,
+
+        /// Label of the group to update.
+        label: String,
+
+        /// Guard or guard field to set (e.g., 'mintLimit' or 'solPayment.value').
+        field: String,
+
+        /// Value to set (JSON or plain string); 'null' removes the guard or field.
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GuardRouteCommand {
+    /// Initialize the allocation tracker of the allocation guard.
+    Allocation {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Priority fee value
+        #[clap(short, long, default_value_t = DEFAULT_PRIORITY_FEE)]
+        priority_fee: u64,
+
+        /// Address of candy guard [defaults to cache value].
+        #[clap(long)]
+        candy_guard: Option<String>,
+
+        /// Address of candy machine [defaults to cache value].
+        #[clap(long)]
+        candy_machine: Option<String>,
+
+        /// Candy guard group label [defaults to all groups with an allocation guard].
+        #[clap(long)]
+        group: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AirdropCommand {
+    /// Reconcile the transactions of an interrupted airdrop
+    Reconcile {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the airdrop results file.
+        #[clap(long, default_value = DEFAULT_AIRDROP_RESULTS)]
+        results: String,
     },
 }
 
@@ -605,7 +1063,7 @@ pub enum GuardCommand {
 pub enum FreezeCommand {
     /// Initialize the freeze escrow account.
     Initialize {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -643,7 +1101,7 @@ pub enum FreezeCommand {
     },
     /// Thaw a NFT or all NFTs in a candy guard.
     Thaw {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -700,7 +1158,7 @@ pub enum FreezeCommand {
     },
     /// Unlock treasury funds after freeze is turned off or expires.
     UnlockFunds {
-        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
 
@@ -741,3 +1199,35 @@ pub enum FreezeCommand {
         token: bool,
     },
 }
+
+#[derive(Subcommand)]
+pub enum TxCommand {
+    /// Create a durable nonce account for long-running or offline-signed transactions
+    CreateNonce {
+        /// Path to the keypair file (or env:<VAR>, prompt:[<DERIVATION_PATH>] or command:<SIGNER>), uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Authority of the nonce account (e.g., a multisig), defaults to the keypair
+        #[clap(long)]
+        authority: Option<String>,
+    },
+
+    /// Broadcast transactions exported with --export-tx and signed externally
+    Submit {
+        /// Path to the file with the signed transactions (exported file or one transaction per line)
+        file: String,
+
+        /// Path to a keypair file to sign the transactions with, if it is a required signer
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+    },
+}
@@ -22,6 +22,7 @@ use crate::{
     config::get_config_data,
     hash::hash_and_update,
     pdas::*,
+    tx::{get_tx_authority, send_or_export, TxExport},
     update::{process_update, UpdateArgs},
     utils::{assert_correct_authority, spinner_with_style},
 };
@@ -34,6 +35,8 @@ pub struct SetCollectionArgs {
     pub config: String,
     pub candy_machine: Option<String>,
     pub priority_fee: u64,
+    pub export_tx: Option<TxExport>,
+    pub nonce: Option<Pubkey>,
 }
 
 pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
@@ -92,7 +95,7 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
     pb.finish_with_message("Done");
 
     assert_correct_authority(
-        &sugar_config.keypair.pubkey(),
+        &get_tx_authority(&args.export_tx, sugar_config.keypair.pubkey()),
         &candy_machine_state.authority,
     )?;
 
@@ -103,7 +106,11 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
     );
 
     let pb = spinner_with_style();
-    pb.set_message("Sending set collection transaction...");
+    pb.set_message(if args.export_tx.is_some() {
+        "Exporting set collection transaction..."
+    } else {
+        "Sending set collection transaction..."
+    });
 
     let set_signature = set_collection(
         &program,
@@ -112,14 +119,19 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
         &collection_mint_pubkey,
         &collection_metadata_info,
         &collection_edition_info,
+        &sugar_config.keypair,
         &args,
     )?;
 
-    pb.finish_with_message(format!(
-        "{} {}",
-        style("Set collection signature:").bold(),
-        set_signature
-    ));
+    if let Some(set_signature) = set_signature {
+        pb.finish_with_message(format!(
+            "{} {}",
+            style("Set collection signature:").bold(),
+            set_signature
+        ));
+    } else {
+        pb.finish_with_message("Set collection transaction exported");
+    }
 
     // If a candy machine id wasn't manually specified we are operating on the candy machine in the cache
     // and so need to update the cache file.
@@ -151,6 +163,8 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
                 config: args.config,
                 candy_machine: Some(candy_machine_id),
                 priority_fee: args.priority_fee,
+                export_tx: args.export_tx,
+                nonce: args.nonce,
             };
 
             process_update(update_args)?;
@@ -160,6 +174,7 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn set_collection<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     candy_pubkey: &Pubkey,
@@ -167,9 +182,10 @@ pub fn set_collection<C: Deref<Target = impl Signer> + Clone>(
     new_collection_mint_pubkey: &Pubkey,
     new_collection_metadata_info: &PdaInfo<Metadata>,
     new_collection_edition_info: &PdaInfo<MasterEditionV2>,
+    signer: &dyn Signer,
     args: &SetCollectionArgs,
-) -> Result<Signature> {
-    let payer = program.payer();
+) -> Result<Option<Signature>> {
+    let payer = get_tx_authority(&args.export_tx, program.payer());
 
     let (authority_pda, _) = find_candy_machine_creator_pda(candy_pubkey);
 
@@ -247,7 +263,13 @@ pub fn set_collection<C: Deref<Target = impl Signer> + Clone>(
         })
         .args(nft_instruction::SetCollectionV2);
 
-    let sig = builder.send()?;
-
-    Ok(sig)
+    send_or_export(
+        &program.rpc(),
+        &builder.instructions()?,
+        signer,
+        &payer,
+        &args.export_tx,
+        &args.nonce,
+        "Set candy machine collection",
+    )
 }
@@ -31,6 +31,8 @@ pub use crate::{
     cache::{Cache, CacheItem},
     constants::*,
     errors::*,
+    output::{push_output, set_output},
     parse::path_to_string,
     setup::{setup_client, sugar_setup},
+    signer::SugarSigner,
 };
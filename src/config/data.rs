@@ -3,18 +3,16 @@ use std::{
     str::FromStr,
 };
 
-use anchor_client::solana_sdk::{
-    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair,
-};
+use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
 pub use anyhow::{anyhow, Result};
 use chrono::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::CandyGuardData;
-use crate::config::errors::*;
+use crate::{config::errors::*, signer::SugarSigner};
 
 pub struct SugarConfig {
-    pub keypair: Keypair,
+    pub keypair: SugarSigner,
     pub rpc_url: String,
 }
 
@@ -154,9 +152,10 @@ where
 }
 
 pub fn parse_string_as_date(go_live_date: &str) -> Result<String> {
+    // dates without an explicit timezone are interpreted as UTC
     let date = dateparser::parse_with(
         go_live_date,
-        &Local,
+        &Utc,
         NaiveTime::from_hms_opt(0, 0, 0).ok_or_else(|| anyhow!("Failed to parse go live date"))?,
     )?;
 
@@ -165,13 +164,173 @@ pub fn parse_string_as_date(go_live_date: &str) -> Result<String> {
 
 pub fn go_live_date_as_timestamp(go_live_date: &Option<String>) -> Result<Option<i64>> {
     if let Some(go_live_date) = go_live_date {
-        let date = dateparser::parse(go_live_date)?;
-        Ok(Some(date.timestamp()))
+        Ok(Some(parse_date_as_timestamp(go_live_date, &[])?))
     } else {
         Ok(None)
     }
 }
 
+/// Parses a date as a UTC timestamp. Dates without an explicit timezone are
+/// interpreted as UTC, so the same config results in the same timestamp on any
+/// machine.
+///
+/// Relative dates are also supported, either relative to the current time
+/// ("+2h" or "now+2h") or to one of the `references` ("startDate+24h"). The
+/// offset units are s (seconds), m (minutes), h (hours), d (days) and w (weeks).
+pub fn parse_date_as_timestamp(date: &str, references: &[(&str, Option<&str>)]) -> Result<i64> {
+    let date = date.trim();
+
+    if let Some((base, offset)) = parse_relative_date(date) {
+        let base = if base.is_empty() || base == "now" {
+            Utc::now().timestamp()
+        } else {
+            match references.iter().find(|(name, _)| *name == base) {
+                // references are resolved without references to avoid cycles
+                Some((_, Some(reference))) => parse_date_as_timestamp(reference, &[])
+                    .map_err(|err| anyhow!("Invalid {base} reference in '{date}': {err}"))?,
+                Some((_, None)) => return Err(anyhow!("Missing {base} referenced by '{date}'")),
+                None => return Err(anyhow!("Unknown date reference '{base}' in '{date}'")),
+            }
+        };
+
+        return base
+            .checked_add(offset)
+            .ok_or_else(|| anyhow!("Invalid date: {date}"));
+    }
+
+    Ok(dateparser::parse_with_timezone(date, &Utc)
+        .map_err(|err| anyhow!("Invalid date '{date}': {err}"))?
+        .timestamp())
+}
+
+/// Splits a relative date ("<base>+<number><unit>") into its base and offset
+/// in seconds.
+fn parse_relative_date(date: &str) -> Option<(&str, i64)> {
+    let index = date.find(['+', '-'])?;
+    let (base, offset) = date.split_at(index);
+
+    if !base.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let offset = &offset[1..];
+    let unit = offset.chars().last()?;
+    let value = offset[..offset.len() - unit.len_utf8()]
+        .parse::<i64>()
+        .ok()?;
+
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return None,
+    };
+
+    Some((base, sign * value.checked_mul(seconds)?))
+}
+
+/// Timezone used to show dates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timezone {
+    /// Local timezone of the system, with the offset in effect at each date
+    /// (e.g., daylight saving time).
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Returns the offset of the timezone at the specified (UTC) date.
+    pub fn offset_at(&self, date: &NaiveDateTime) -> FixedOffset {
+        match self {
+            Timezone::Local => Local.offset_from_utc_datetime(date),
+            Timezone::Fixed(offset) => *offset,
+        }
+    }
+}
+
+/// Parses a timezone, either "UTC", "local" or a fixed offset (e.g., "+02:00").
+pub fn parse_timezone(timezone: &str) -> Result<Timezone> {
+    match timezone.to_lowercase().as_str() {
+        "utc" | "z" => Ok(Timezone::Fixed(Utc.fix())),
+        "local" => Ok(Timezone::Local),
+        _ => {
+            let (sign, offset) = match timezone.split_at(1) {
+                ("+", offset) => (1, offset),
+                ("-", offset) => (-1, offset),
+                _ => return Err(anyhow!("Invalid timezone: {timezone}")),
+            };
+            let digits: String = offset.chars().filter(|c| *c != ':').collect();
+
+            let (hours, minutes) = match digits.len() {
+                2 => (digits.parse::<i32>().ok(), Some(0)),
+                4 => (
+                    digits[..2].parse::<i32>().ok(),
+                    digits[2..].parse::<i32>().ok(),
+                ),
+                _ => (None, None),
+            };
+
+            match (hours, minutes) {
+                (Some(hours), Some(minutes)) if minutes < 60 => {
+                    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+                        .map(Timezone::Fixed)
+                        .ok_or_else(|| anyhow!("Invalid timezone: {timezone}"))
+                }
+                _ => Err(anyhow!("Invalid timezone: {timezone}")),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_date_as_timestamp() {
+    // dates without timezone are UTC
+    assert_eq!(
+        parse_date_as_timestamp("2022-10-20 18:00:00", &[]).unwrap(),
+        1666288800
+    );
+    assert_eq!(
+        parse_date_as_timestamp("2022-10-20 20:00:00 +02:00", &[]).unwrap(),
+        1666288800
+    );
+    // relative to another date
+    let references = [("startDate", Some("2022-10-20 18:00:00 +0000"))];
+    assert_eq!(
+        parse_date_as_timestamp("startDate+24h", &references).unwrap(),
+        1666288800 + 86_400
+    );
+    assert_eq!(
+        parse_date_as_timestamp("startDate-30m", &references).unwrap(),
+        1666288800 - 1800
+    );
+    assert!(parse_date_as_timestamp("endDate+1d", &references).is_err());
+    // relative to now
+    let now = Utc::now().timestamp();
+    let date = parse_date_as_timestamp("+2h", &[]).unwrap();
+    assert!((now + 7200..=now + 7210).contains(&date));
+
+    let date = NaiveDateTime::default();
+    assert_eq!(
+        parse_timezone("+05:30")
+            .unwrap()
+            .offset_at(&date)
+            .local_minus_utc(),
+        19800
+    );
+    assert_eq!(
+        parse_timezone("-03")
+            .unwrap()
+            .offset_at(&date)
+            .local_minus_utc(),
+        -10800
+    );
+    assert_eq!(parse_timezone("local").unwrap(), Timezone::Local);
+    assert!(parse_timezone("Mars/Olympus").is_err());
+}
+
 pub fn price_as_lamports(price: f64) -> u64 {
     (price * LAMPORTS_PER_SOL as f64) as u64
 }
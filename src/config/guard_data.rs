@@ -1,10 +1,17 @@
+use std::fmt;
+
 use anchor_lang::prelude::Pubkey;
 use anyhow::{anyhow, Result};
-use dateparser::DateTimeUtc;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use serde_with::{serde_as, DisplayFromStr};
 
-use super::{data::price_as_lamports, to_pubkey, to_string};
+use super::{
+    base_units_to_ui_amount, data::parse_date_as_timestamp, to_pubkey, to_string,
+    ui_amount_to_base_units,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CandyGuardData {
@@ -18,7 +25,7 @@ impl CandyGuardData {
             let mut group_vec = Vec::with_capacity(groups.len());
 
             for group in groups {
-                group_vec.push(group.to_guard_format()?);
+                group_vec.push(group.to_guard_format_with(&self.default)?);
             }
 
             Some(group_vec)
@@ -46,6 +53,227 @@ impl Group {
             guards: self.guards.to_guard_format()?,
         })
     }
+
+    /// Converts the group, resolving relative dates against the dates of the
+    /// group merged with the default guard set.
+    pub fn to_guard_format_with(
+        &self,
+        default: &GuardSet,
+    ) -> Result<mpl_candy_guard::state::Group> {
+        Ok(mpl_candy_guard::state::Group {
+            label: self.label.clone(),
+            guards: self.guards.to_guard_format_with(Some(default))?,
+        })
+    }
+}
+
+/// Token amount of a guard, either in base units (e.g., `1250000`) or in UI
+/// units as a decimal string or number (e.g., `"1.25"` or `1.25`). UI amounts
+/// are converted using the decimals of the mint before the guards are sent
+/// on-chain.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TokenAmount {
+    Base(u64),
+    Ui(String),
+}
+
+impl Default for TokenAmount {
+    fn default() -> Self {
+        TokenAmount::Base(0)
+    }
+}
+
+impl From<u64> for TokenAmount {
+    fn from(amount: u64) -> Self {
+        TokenAmount::Base(amount)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenAmount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TokenAmountVisitor;
+
+        impl<'de> Visitor<'de> for TokenAmountVisitor {
+            type Value = TokenAmount;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "an integer amount in base units (e.g., 1250000) or a decimal amount in UI \
+                    units (e.g., \"1.25\")",
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, amount: u64) -> Result<Self::Value, E> {
+                Ok(TokenAmount::Base(amount))
+            }
+
+            fn visit_i64<E: de::Error>(self, amount: i64) -> Result<Self::Value, E> {
+                u64::try_from(amount)
+                    .map(TokenAmount::Base)
+                    .map_err(|_| E::custom(format!("invalid token amount {amount}: negative")))
+            }
+
+            // decimal numbers are UI amounts, read through their shortest decimal
+            // representation; amounts with more significant digits than a JSON
+            // number can hold must be quoted
+            fn visit_f64<E: de::Error>(self, amount: f64) -> Result<Self::Value, E> {
+                if amount.is_sign_negative() || !amount.is_finite() {
+                    return Err(E::custom(format!("invalid token amount {amount}")));
+                }
+
+                Ok(TokenAmount::Ui(amount.to_string()))
+            }
+
+            fn visit_str<E: de::Error>(self, amount: &str) -> Result<Self::Value, E> {
+                Ok(TokenAmount::Ui(amount.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(TokenAmountVisitor)
+    }
+}
+
+impl TokenAmount {
+    /// Returns the amount in base units using the specified mint decimals.
+    pub fn to_base_units(&self, decimals: u8) -> Result<u64> {
+        match self {
+            TokenAmount::Base(amount) => Ok(*amount),
+            TokenAmount::Ui(amount) => ui_amount_to_base_units(amount, decimals),
+        }
+    }
+
+    /// Returns the amount in base units, failing if the amount is in UI units
+    /// and has not been resolved yet.
+    pub fn base_units(&self) -> Result<u64> {
+        match self {
+            TokenAmount::Base(amount) => Ok(*amount),
+            TokenAmount::Ui(amount) => Err(anyhow!(
+                "Token amount '{amount}' needs the mint decimals to be converted to base units"
+            )),
+        }
+    }
+}
+
+/// Decimals of SOL amounts.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Deserializes an amount in SOL (e.g., `1.5` or `"1.5"`) as a UI token amount.
+/// Numbers are read through their shortest decimal representation; amounts
+/// with more significant digits than a JSON number can hold must be strings.
+pub fn to_sol_amount<'de, D>(deserializer: D) -> Result<TokenAmount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SolAmount {
+        Integer(u64),
+        Decimal(f64),
+        Text(String),
+    }
+
+    let amount = match SolAmount::deserialize(deserializer)? {
+        SolAmount::Integer(amount) => amount.to_string(),
+        SolAmount::Decimal(amount) => amount.to_string(),
+        SolAmount::Text(amount) => amount,
+    };
+
+    ui_amount_to_base_units(&amount, SOL_DECIMALS).map_err(serde::de::Error::custom)?;
+
+    Ok(TokenAmount::Ui(amount))
+}
+
+/// Serializes an amount in SOL as a number, or as a decimal string if the
+/// number would not represent it exactly.
+pub fn sol_amount_to_value<S>(amount: &TokenAmount, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let amount = match amount {
+        TokenAmount::Base(lamports) => base_units_to_ui_amount(*lamports, SOL_DECIMALS),
+        TokenAmount::Ui(amount) => amount.clone(),
+    };
+
+    if let Ok(value) = amount.parse::<u64>() {
+        return serializer.serialize_u64(value);
+    }
+
+    match amount.parse::<f64>() {
+        Ok(value) if value.to_string() == amount => serializer.serialize_f64(value),
+        _ => serializer.serialize_str(&amount),
+    }
+}
+
+impl CandyGuardData {
+    /// Returns the (location, guard name, mint, amount) of every token amount
+    /// of the guards configuration.
+    pub fn token_amounts(&self) -> Vec<(&str, &'static str, Pubkey, &TokenAmount)> {
+        let mut amounts = Vec::new();
+        let mut guard_sets = vec![("default", &self.default)];
+
+        if let Some(groups) = &self.groups {
+            for group in groups {
+                guard_sets.push((group.label.as_str(), &group.guards));
+            }
+        }
+
+        for (location, guard_set) in guard_sets {
+            if let Some(guard) = &guard_set.token_payment {
+                amounts.push((location, "tokenPayment", guard.mint, &guard.amount));
+            }
+            if let Some(guard) = &guard_set.freeze_token_payment {
+                amounts.push((location, "freezeTokenPayment", guard.mint, &guard.amount));
+            }
+            if let Some(guard) = &guard_set.token_burn {
+                amounts.push((location, "tokenBurn", guard.mint, &guard.amount));
+            }
+            if let Some(guard) = &guard_set.token_gate {
+                amounts.push((location, "tokenGate", guard.mint, &guard.amount));
+            }
+            if let Some(guard) = &guard_set.token2022_payment {
+                amounts.push((location, "token2022Payment", guard.mint, &guard.amount));
+            }
+        }
+
+        amounts
+    }
+
+    /// Returns the mint and a mutable reference of every token amount of the
+    /// guards configuration.
+    pub fn token_amounts_mut(&mut self) -> Vec<(Pubkey, &mut TokenAmount)> {
+        let mut amounts = Vec::new();
+        let mut guard_sets = vec![&mut self.default];
+
+        if let Some(groups) = &mut self.groups {
+            for group in groups {
+                guard_sets.push(&mut group.guards);
+            }
+        }
+
+        for guard_set in guard_sets {
+            if let Some(guard) = &mut guard_set.token_payment {
+                amounts.push((guard.mint, &mut guard.amount));
+            }
+            if let Some(guard) = &mut guard_set.freeze_token_payment {
+                amounts.push((guard.mint, &mut guard.amount));
+            }
+            if let Some(guard) = &mut guard_set.token_burn {
+                amounts.push((guard.mint, &mut guard.amount));
+            }
+            if let Some(guard) = &mut guard_set.token_gate {
+                amounts.push((guard.mint, &mut guard.amount));
+            }
+            if let Some(guard) = &mut guard_set.token2022_payment {
+                amounts.push((guard.mint, &mut guard.amount));
+            }
+        }
+
+        amounts
+    }
 }
 
 /// The set of guards available.
@@ -96,8 +324,37 @@ pub struct GuardSet {
     pub token2022_payment: Option<Token2022Payment>,
 }
 
+impl GuardSet {
+    /// Returns the start and end dates of the guard set, falling back to the
+    /// dates of the default guard set.
+    pub fn merged_dates<'a>(
+        &'a self,
+        default: Option<&'a GuardSet>,
+    ) -> (Option<&'a StartDate>, Option<&'a EndDate>) {
+        (
+            self.start_date
+                .as_ref()
+                .or(default.and_then(|default| default.start_date.as_ref())),
+            self.end_date
+                .as_ref()
+                .or(default.and_then(|default| default.end_date.as_ref())),
+        )
+    }
+}
+
 impl GuardSet {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::GuardSet> {
+        self.to_guard_format_with(None)
+    }
+
+    /// Converts the guard set. Relative dates (e.g., `startDate+24h`) are
+    /// resolved against the dates of the guard set merged with the default
+    /// guard set, when specified.
+    pub fn to_guard_format_with(
+        &self,
+        default: Option<&GuardSet>,
+    ) -> Result<mpl_candy_guard::guards::GuardSet> {
+        let (merged_start_date, merged_end_date) = self.merged_dates(default);
         // bot tax
         let bot_tax = if let Some(bot_tax) = &self.bot_tax {
             Some(bot_tax.to_guard_format()?)
@@ -118,7 +375,7 @@ impl GuardSet {
         };
         // start_date
         let start_date = if let Some(start_date) = &self.start_date {
-            Some(start_date.to_guard_format()?)
+            Some(start_date.to_guard_format_with(merged_end_date)?)
         } else {
             None
         };
@@ -142,7 +399,7 @@ impl GuardSet {
         };
         // end date
         let end_date = if let Some(end_date) = &self.end_date {
-            Some(end_date.to_guard_format()?)
+            Some(end_date.to_guard_format_with(merged_start_date)?)
         } else {
             None
         };
@@ -290,7 +547,10 @@ impl AllowList {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct BotTax {
-    pub value: f64,
+    /// Amount in SOL (`TokenAmount::Base` holds lamports).
+    #[serde(deserialize_with = "to_sol_amount")]
+    #[serde(serialize_with = "sol_amount_to_value")]
+    pub value: TokenAmount,
 
     pub last_instruction: bool,
 }
@@ -298,7 +558,7 @@ pub struct BotTax {
 impl BotTax {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::BotTax> {
         Ok(mpl_candy_guard::guards::BotTax {
-            lamports: price_as_lamports(self.value),
+            lamports: self.value.to_base_units(SOL_DECIMALS)?,
             last_instruction: self.last_instruction,
         })
     }
@@ -313,7 +573,19 @@ pub struct EndDate {
 
 impl EndDate {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::EndDate> {
-        let timestamp = self.date.parse::<DateTimeUtc>()?.0.timestamp();
+        self.to_guard_format_with(None)
+    }
+
+    /// Converts the end date, which can be relative to the start date of the
+    /// same guard set (e.g., "startDate+24h").
+    pub fn to_guard_format_with(
+        &self,
+        start_date: Option<&StartDate>,
+    ) -> Result<mpl_candy_guard::guards::EndDate> {
+        let timestamp = parse_date_as_timestamp(
+            &self.date,
+            &[("startDate", start_date.map(|date| date.date.as_str()))],
+        )?;
 
         Ok(mpl_candy_guard::guards::EndDate { date: timestamp })
     }
@@ -436,7 +708,10 @@ impl RedeemedAmount {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct SolPayment {
-    pub value: f64,
+    /// Amount in SOL (`TokenAmount::Base` holds lamports).
+    #[serde(deserialize_with = "to_sol_amount")]
+    #[serde(serialize_with = "sol_amount_to_value")]
+    pub value: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -446,7 +721,7 @@ pub struct SolPayment {
 impl SolPayment {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::SolPayment> {
         Ok(mpl_candy_guard::guards::SolPayment {
-            lamports: price_as_lamports(self.value),
+            lamports: self.value.to_base_units(SOL_DECIMALS)?,
             destination: self.destination,
         })
     }
@@ -459,7 +734,20 @@ pub struct StartDate {
 
 impl StartDate {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::StartDate> {
-        let timestamp = self.date.parse::<DateTimeUtc>()?.0.timestamp();
+        self.to_guard_format_with(None)
+    }
+
+    /// Converts the start date, which can be relative to the end date of the
+    /// same guard set (e.g., "endDate-24h").
+    pub fn to_guard_format_with(
+        &self,
+        end_date: Option<&EndDate>,
+    ) -> Result<mpl_candy_guard::guards::StartDate> {
+        let timestamp = parse_date_as_timestamp(
+            &self.date,
+            &[("endDate", end_date.map(|date| date.date.as_str()))],
+        )?;
+
         Ok(mpl_candy_guard::guards::StartDate { date: timestamp })
     }
 }
@@ -486,7 +774,7 @@ impl ThirdPartySigner {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TokenBurn {
-    pub amount: u64,
+    pub amount: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -496,7 +784,7 @@ pub struct TokenBurn {
 impl TokenBurn {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::TokenBurn> {
         Ok(mpl_candy_guard::guards::TokenBurn {
-            amount: self.amount,
+            amount: self.amount.base_units()?,
             mint: self.mint,
         })
     }
@@ -506,7 +794,7 @@ impl TokenBurn {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TokenGate {
-    pub amount: u64,
+    pub amount: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -516,7 +804,7 @@ pub struct TokenGate {
 impl TokenGate {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::TokenGate> {
         Ok(mpl_candy_guard::guards::TokenGate {
-            amount: self.amount,
+            amount: self.amount.base_units()?,
             mint: self.mint,
         })
     }
@@ -527,7 +815,7 @@ impl TokenGate {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenPayment {
-    pub amount: u64,
+    pub amount: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -541,7 +829,7 @@ pub struct TokenPayment {
 impl TokenPayment {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::TokenPayment> {
         Ok(mpl_candy_guard::guards::TokenPayment {
-            amount: self.amount,
+            amount: self.amount.base_units()?,
             mint: self.mint,
             destination_ata: self.destination_ata,
         })
@@ -553,7 +841,10 @@ impl TokenPayment {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct FreezeSolPayment {
-    pub value: f64,
+    /// Amount in SOL (`TokenAmount::Base` holds lamports).
+    #[serde(deserialize_with = "to_sol_amount")]
+    #[serde(serialize_with = "sol_amount_to_value")]
+    pub value: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -563,7 +854,7 @@ pub struct FreezeSolPayment {
 impl FreezeSolPayment {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::FreezeSolPayment> {
         Ok(mpl_candy_guard::guards::FreezeSolPayment {
-            lamports: price_as_lamports(self.value),
+            lamports: self.value.to_base_units(SOL_DECIMALS)?,
             destination: self.destination,
         })
     }
@@ -574,7 +865,7 @@ impl FreezeSolPayment {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct FreezeTokenPayment {
-    pub amount: u64,
+    pub amount: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -588,7 +879,7 @@ pub struct FreezeTokenPayment {
 impl FreezeTokenPayment {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::FreezeTokenPayment> {
         Ok(mpl_candy_guard::guards::FreezeTokenPayment {
-            amount: self.amount,
+            amount: self.amount.base_units()?,
             mint: self.mint,
             destination_ata: self.destination_ata,
         })
@@ -636,7 +927,7 @@ impl Allocation {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Token2022Payment {
-    pub amount: u64,
+    pub amount: TokenAmount,
 
     #[serde(deserialize_with = "to_pubkey")]
     #[serde(serialize_with = "to_string")]
@@ -650,9 +941,81 @@ pub struct Token2022Payment {
 impl Token2022Payment {
     pub fn to_guard_format(&self) -> Result<mpl_candy_guard::guards::Token2022Payment> {
         Ok(mpl_candy_guard::guards::Token2022Payment {
-            amount: self.amount,
+            amount: self.amount.base_units()?,
             mint: self.mint,
             destination_ata: self.destination_ata,
         })
     }
 }
+
+#[test]
+fn test_sol_amount() {
+    let sol_payment = |value: &str| {
+        serde_json::from_str::<SolPayment>(&format!(
+            r#"{{ "value": {value}, "destination": "11111111111111111111111111111111" }}"#
+        ))
+    };
+
+    for (value, lamports) in [
+        ("0.3", 300_000_000),
+        ("1", 1_000_000_000),
+        ("1.000000001", 1_000_000_001),
+        ("\"1.50\"", 1_500_000_000),
+    ] {
+        let guard = sol_payment(value).unwrap();
+        assert_eq!(guard.to_guard_format().unwrap().lamports, lamports);
+        assert_eq!(
+            serde_json::to_value(&guard).unwrap()["value"],
+            serde_json::from_str::<serde_json::Value>(value).unwrap()
+        );
+    }
+
+    assert!(sol_payment("0.0000000001").is_err());
+    assert!(sol_payment("-1").is_err());
+}
+
+#[test]
+fn test_token_amount() {
+    let amount = |value: &str| serde_json::from_str::<TokenAmount>(value);
+
+    assert_eq!(amount("1250000").unwrap(), TokenAmount::Base(1_250_000));
+    assert_eq!(
+        amount("\"1.25\"").unwrap(),
+        TokenAmount::Ui("1.25".to_string())
+    );
+    assert_eq!(amount("1.5").unwrap(), TokenAmount::Ui("1.5".to_string()));
+    assert_eq!(amount("1.5").unwrap().to_base_units(6).unwrap(), 1_500_000);
+    assert!(amount("-1").is_err());
+    assert!(amount("-1.5").is_err());
+    assert!(amount("true").is_err());
+}
+
+#[test]
+fn test_relative_dates_resolve_against_default() {
+    let guards: CandyGuardData = serde_json::from_str(
+        r#"{
+            "default": {
+                "startDate": { "date": "2022-10-20 18:00:00 +0000" }
+            },
+            "groups": [
+                {
+                    "label": "public",
+                    "guards": {
+                        "endDate": { "date": "startDate+24h" }
+                    }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let guards = guards.to_guard_format().unwrap();
+    let end_date = guards.groups.unwrap()[0]
+        .guards
+        .end_date
+        .as_ref()
+        .unwrap()
+        .date;
+
+    assert_eq!(end_date, 1666375200);
+}
@@ -11,11 +11,6 @@ pub use errors::*;
 pub use guard_data::*;
 pub use parser::*;
 use serde::{Deserialize, Deserializer, Serializer};
-use solana_program::native_token::LAMPORTS_PER_SOL;
-
-pub fn price_as_lamports(price: f64) -> u64 {
-    (price * LAMPORTS_PER_SOL as f64) as u64
-}
 
 pub fn to_string<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -32,3 +27,61 @@ where
     let s: String = Deserialize::deserialize(deserializer)?;
     Pubkey::from_str(&s).map_err(serde::de::Error::custom)
 }
+
+/// Converts a decimal amount in UI units (e.g., "1.25") to base units without
+/// going through floating point arithmetic.
+pub fn ui_amount_to_base_units(amount: &str, decimals: u8) -> Result<u64> {
+    let amount = amount.trim();
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(anyhow!("Invalid amount: {amount}"));
+    }
+
+    if fraction.len() > decimals as usize {
+        return Err(anyhow!(
+            "Amount {amount} has more than {decimals} decimal place(s)"
+        ));
+    }
+
+    let digits = format!("{integer}{fraction:0<width$}", width = decimals as usize);
+
+    digits
+        .parse::<u128>()
+        .ok()
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or_else(|| anyhow!("Amount {amount} is too large"))
+}
+
+/// Converts an amount in base units to a decimal string in UI units.
+pub fn base_units_to_ui_amount(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{amount:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+#[test]
+fn test_ui_amount_conversion() {
+    assert_eq!(ui_amount_to_base_units("1.25", 9).unwrap(), 1_250_000_000);
+    assert_eq!(ui_amount_to_base_units("0.3", 9).unwrap(), 300_000_000);
+    assert_eq!(ui_amount_to_base_units("42", 0).unwrap(), 42);
+    assert_eq!(ui_amount_to_base_units(".5", 2).unwrap(), 50);
+    assert!(ui_amount_to_base_units("0.001", 2).is_err());
+    assert!(ui_amount_to_base_units("-1", 2).is_err());
+    assert!(ui_amount_to_base_units("1e3", 2).is_err());
+    assert!(ui_amount_to_base_units("18446744073709551616", 0).is_err());
+
+    assert_eq!(base_units_to_ui_amount(1_250_000_000, 9), "1.25");
+    assert_eq!(base_units_to_ui_amount(5, 6), "0.000005");
+    assert_eq!(base_units_to_ui_amount(42, 0), "42");
+}
@@ -1,9 +1,12 @@
 use std::{
-    fs::{metadata, OpenOptions},
+    fs::{metadata, File, OpenOptions},
     io::ErrorKind,
+    path::Path,
 };
 
 use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
 use tracing::error;
 
 use crate::config::{data::*, errors::ConfigError};
@@ -39,3 +42,45 @@ pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
     };
     Ok(config_data)
 }
+
+/// Reads the config file as a JSON value, so that it can be edited and saved
+/// without changing the fields that are not edited.
+pub fn get_config_value(config_path: &str) -> Result<Value> {
+    // makes sure that the config file is valid
+    get_config_data(config_path)?;
+
+    let file = File::open(config_path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+pub fn save_config_value(config_path: &str, config: &Value) -> Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(Path::new(config_path))?;
+
+    serde_json::to_writer_pretty(file, config)?;
+
+    Ok(())
+}
+
+/// Serializes the value to JSON, leaving out the fields that are not set
+/// (`null`).
+pub fn to_value_without_nulls<T: Serialize>(value: &T) -> Result<Value> {
+    fn remove_nulls(value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                object.retain(|_, value| !value.is_null());
+                object.values_mut().for_each(remove_nulls);
+            }
+            Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+            _ => (),
+        }
+    }
+
+    let mut value = serde_json::to_value(value)?;
+    remove_nulls(&mut value);
+
+    Ok(value)
+}
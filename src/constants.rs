@@ -6,6 +6,9 @@ pub use mpl_token_metadata::state::{
 /// Metaplex program id.
 pub const METAPLEX_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 
+/// Token-2022 program id.
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS14aWcHPoK91Xwt6Hs3ePN";
+
 pub const STRING_LEN_SIZE: usize = 4;
 
 pub const CONFIG_CHUNK_SIZE: usize = 10;
@@ -32,7 +35,26 @@ pub const DEFAULT_CACHE: &str = "cache.json";
 
 /// Default path for airdrop list
 pub const DEFAULT_AIRDROP_LIST: &str = "airdrop_list.json";
-pub const DEFAULT_AIRDROP_LIST_HELP: &str = "Path to airdrop targets list, format: \n{\n\"address1\": number_of_tokens,\n\"address2\": number_of_tokens\n}\n";
+pub const DEFAULT_AIRDROP_LIST_HELP: &str = "Path to airdrop targets list, format: \n{\n\"address1\": number_of_tokens,\n\"address2\": number_of_tokens\n}\n\nAlso accepts a CSV (.csv) file with an 'address,count[,label,group,notes]' header or a JSON lines (.jsonl) file with one '{\"address\": ..., \"count\": ...}' object per line (a JSON array of these objects is also accepted, e.g. a snapshot)\n";
+
+/// Default path for airdrop results file.
+pub const DEFAULT_AIRDROP_RESULTS: &str = "airdrop_results.json";
+
+/// Default path for holder snapshot file.
+pub const DEFAULT_SNAPSHOT: &str = "snapshot.csv";
+
+/// Default path for the mint list file.
+pub const DEFAULT_MINTS: &str = "mints.csv";
+
+/// Name of the cache of the mints found in the transaction history, kept in the
+/// directory of the cache file.
+pub const MINT_HISTORY_CACHE_FILE: &str = "mint_history_cache.json";
+
+/// Name of the mint registry file, kept in the directory of the cache file.
+pub const MINT_REGISTRY_FILE: &str = "mint_registry.jsonl";
+
+/// Default path for the deep verification report.
+pub const DEFAULT_VERIFY_REPORT: &str = "verify_report.json";
 
 /// Default path for config file.
 pub const DEFAULT_CONFIG: &str = "config.json";
@@ -98,4 +120,14 @@ pub const MAX_FREEZE_DAYS: u8 = 31;
 
 pub const COMPUTE_UNITS: u32 = 400_000;
 
+/// Compute unit limit of each instruction of a transaction without a compute
+/// unit limit instruction.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u32 = 200_000;
+
 pub const DEFAULT_PRIORITY_FEE: u64 = 500;
+
+/// Default number of parallel mints.
+pub const DEFAULT_MINT_CONCURRENCY: usize = 10;
+
+/// Default number of retries of a mint.
+pub const DEFAULT_MINT_RETRIES: u32 = 3;
@@ -6,9 +6,7 @@ use std::{
     },
 };
 
-use anchor_client::solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, pubkey::Pubkey, signature::Keypair,
-};
+use anchor_client::solana_sdk::{compute_budget::ComputeBudgetInstruction, pubkey::Pubkey};
 use anyhow::Result;
 use console::style;
 use futures::future::select_all;
@@ -30,21 +28,56 @@ const MAX_TRANSACTION_BYTES: usize = 966;
 /// The maximum number of config lines per transaction.
 const MAX_TRANSACTION_LINES: usize = 17;
 
+/// The maximum config line bytes per transaction when config lines are packed,
+/// which need an additional compute unit limit instruction (8 bytes).
+const MAX_PACKED_TRANSACTION_BYTES: usize = 958;
+
+/// The maximum number of config lines per transaction when config lines are
+/// packed.
+const MAX_PACKED_TRANSACTION_LINES: usize = 100;
+
+/// Compute units requested per config line when config lines are packed; the
+/// default limit (200k) is what restricts a transaction to 17 lines.
+const COMPUTE_UNITS_PER_LINE: u32 = 12_000;
+
 pub struct TxInfo {
     candy_pubkey: Pubkey,
-    payer: Keypair,
+    payer: SugarSigner,
     chunk: Vec<(u32, ConfigLine)>,
 }
 
-/// Determine the config lines that need to be uploaded.
+/// Returns the compute unit limit of a transaction with the given number of
+/// config lines. Unless packed, transactions do not set a limit, so the default
+/// limit of their single `add_config_lines` instruction applies.
+pub fn config_lines_compute_units(lines: usize, packed: bool) -> u32 {
+    if packed {
+        COMPUTE_UNITS_PER_LINE * lines as u32
+    } else {
+        DEFAULT_INSTRUCTION_COMPUTE_UNITS
+    }
+}
+
+/// Returns the maximum (bytes, lines) of config lines per transaction.
+fn transaction_limits(packed: bool) -> (usize, usize) {
+    if packed {
+        (MAX_PACKED_TRANSACTION_BYTES, MAX_PACKED_TRANSACTION_LINES)
+    } else {
+        (MAX_TRANSACTION_BYTES, MAX_TRANSACTION_LINES)
+    }
+}
+
+/// Determine the config lines that need to be uploaded. When `packed` is set,
+/// more config lines are added to each transaction.
 pub fn generate_config_lines(
     num_items: u64,
     cache_items: &CacheItems,
     data: &CandyMachineData,
+    packed: bool,
 ) -> Result<Vec<Vec<(u32, ConfigLine)>>> {
     let mut config_lines: Vec<Vec<(u32, ConfigLine)>> = Vec::new();
     let mut current: Vec<(u32, ConfigLine)> = Vec::new();
     let mut tx_size = 0;
+    let (max_bytes, max_lines) = transaction_limits(packed);
 
     let config_line_settings = if let Some(config_line_settings) = &data.config_line_settings {
         config_line_settings
@@ -82,7 +115,7 @@ pub fn generate_config_lines(
 
             let size = (2 * STRING_LEN_SIZE) + data.get_config_line_size();
 
-            if (tx_size + size) > MAX_TRANSACTION_BYTES || current.len() == MAX_TRANSACTION_LINES {
+            if (tx_size + size) > max_bytes || current.len() == max_lines {
                 // we need a separate tx to not break the size limit
                 config_lines.push(current);
                 current = Vec::new();
@@ -101,7 +134,8 @@ pub fn generate_config_lines(
     Ok(config_lines)
 }
 
-/// Send the config lines to the candy machine program.
+/// Send the config lines to the candy machine program. When `packed` is set, the
+/// transactions request a compute unit limit for the number of config lines.
 pub async fn upload_config_lines(
     sugar_config: Arc<SugarConfig>,
     candy_pubkey: Pubkey,
@@ -109,6 +143,7 @@ pub async fn upload_config_lines(
     config_lines: Vec<Vec<(u32, ConfigLine)>>,
     interrupted: Arc<AtomicBool>,
     priority_fee: u64,
+    packed: bool,
 ) -> Result<Vec<DeployError>> {
     println!(
         "Sending config line(s) in {} transaction(s): (Ctrl+C to abort)",
@@ -123,12 +158,9 @@ pub async fn upload_config_lines(
     let mut transactions = Vec::new();
 
     for chunk in config_lines {
-        let keypair = bs58::encode(sugar_config.keypair.to_bytes()).into_string();
-        let payer = Keypair::from_base58_string(&keypair);
-
         transactions.push(TxInfo {
             candy_pubkey,
-            payer,
+            payer: sugar_config.keypair.clone(),
             chunk,
         });
     }
@@ -138,7 +170,7 @@ pub async fn upload_config_lines(
     for tx in transactions.drain(0..cmp::min(transactions.len(), PARALLEL_LIMIT)) {
         let config = sugar_config.clone();
         handles.push(tokio::spawn(async move {
-            add_config_lines(config, tx, priority_fee).await
+            add_config_lines(config, tx, priority_fee, packed).await
         }));
     }
 
@@ -187,7 +219,7 @@ pub async fn upload_config_lines(
                 for tx in transactions.drain(0..cmp::min(transactions.len(), PARALLEL_LIMIT / 2)) {
                     let config = sugar_config.clone();
                     handles.push(tokio::spawn(async move {
-                        add_config_lines(config, tx, priority_fee).await
+                        add_config_lines(config, tx, priority_fee, packed).await
                     }));
                 }
             }
@@ -220,6 +252,7 @@ pub async fn add_config_lines(
     config: Arc<SugarConfig>,
     tx_info: TxInfo,
     priority_fee: u64,
+    packed: bool,
 ) -> Result<Vec<u32>> {
     let client = setup_client(&config)?;
     let program = client.program(CANDY_MACHINE_ID);
@@ -239,9 +272,15 @@ pub async fn add_config_lines(
 
     let priority_fee = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
 
-    let _sig = program
-        .request()
-        .instruction(priority_fee)
+    let mut request = program.request().instruction(priority_fee);
+
+    if packed {
+        request = request.instruction(ComputeBudgetInstruction::set_compute_unit_limit(
+            config_lines_compute_units(config_lines.len(), true),
+        ));
+    }
+
+    request
         .accounts(nft_accounts::AddConfigLines {
             candy_machine: tx_info.candy_pubkey,
             authority: program.payer(),
@@ -255,3 +294,107 @@ pub async fn add_config_lines(
 
     Ok(indices)
 }
+
+#[test]
+fn test_generate_packed_config_lines() {
+    use mpl_candy_machine_core::ConfigLineSettings;
+
+    let data = CandyMachineData {
+        items_available: 200,
+        symbol: String::new(),
+        seller_fee_basis_points: 0,
+        max_supply: 0,
+        is_mutable: true,
+        creators: Vec::new(),
+        config_line_settings: Some(ConfigLineSettings {
+            prefix_name: "Sugar #".to_string(),
+            name_length: 3,
+            prefix_uri: "https://arweave.net/".to_string(),
+            uri_length: 2,
+            is_sequential: false,
+        }),
+        hidden_settings: None,
+    };
+
+    let mut cache_items = CacheItems::new();
+
+    for i in 0..200 {
+        cache_items.insert(
+            i.to_string(),
+            CacheItem {
+                name: format!("Sugar #{i}"),
+                image_hash: String::new(),
+                image_link: String::new(),
+                metadata_hash: String::new(),
+                metadata_link: format!("https://arweave.net/{i}"),
+                on_chain: false,
+                animation_hash: None,
+                animation_link: None,
+            },
+        );
+    }
+
+    // short config lines are limited by the number of lines per transaction
+    let config_lines = generate_config_lines(200, &cache_items, &data, false).unwrap();
+    assert_eq!(config_lines.len(), 12);
+    assert_eq!(config_lines[0].len(), MAX_TRANSACTION_LINES);
+
+    let config_lines = generate_config_lines(200, &cache_items, &data, true).unwrap();
+    assert_eq!(config_lines.len(), 3);
+    assert_eq!(config_lines[0].len(), 73);
+}
+
+#[test]
+fn test_config_lines_transaction_size() {
+    use anchor_client::{
+        anchor_lang::{InstructionData, ToAccountMetas},
+        solana_sdk::{
+            hash::Hash, instruction::Instruction, packet::PACKET_DATA_SIZE, signature::Keypair,
+            transaction::Transaction,
+        },
+    };
+
+    let payer = Keypair::new();
+    let candy_machine = Pubkey::new_unique();
+
+    for packed in [false, true] {
+        let (max_bytes, _) = transaction_limits(packed);
+        // a single config line using all the bytes available
+        let config_line = ConfigLine {
+            name: "x".repeat(32),
+            uri: "x".repeat(max_bytes - 2 * STRING_LEN_SIZE - 32),
+        };
+
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_price(1)];
+
+        if packed {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                config_lines_compute_units(1, true),
+            ));
+        }
+
+        instructions.push(Instruction {
+            program_id: CANDY_MACHINE_ID,
+            accounts: nft_accounts::AddConfigLines {
+                candy_machine,
+                authority: payer.pubkey(),
+            }
+            .to_account_metas(None),
+            data: nft_instruction::AddConfigLines {
+                index: 0,
+                config_lines: vec![config_line],
+            }
+            .data(),
+        });
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let size = bincode::serialize(&tx).unwrap().len();
+
+        assert!(size <= PACKET_DATA_SIZE, "{size} bytes (packed: {packed})");
+    }
+}
@@ -0,0 +1,168 @@
+use std::ops::Deref;
+
+use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, program_pack::Pack};
+use anyhow::Result;
+use console::style;
+use mpl_candy_machine_core::{CandyMachineData, ConfigLine};
+use mpl_token_metadata::state::{
+    MetadataDelegateRecord, TokenMetadataAccount, MAX_MASTER_EDITION_LEN, MAX_METADATA_LEN,
+};
+use spl_token::state::Account as SplAccount;
+
+use crate::{common::*, deploy::config_lines_compute_units};
+
+/// Fee per transaction signature (in lamports).
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+/// Estimated cost of a deploy, in lamports.
+#[derive(Debug, Default)]
+pub struct DeployCost {
+    /// Size of the candy machine account, when it needs to be created.
+    pub candy_machine_size: Option<usize>,
+    pub candy_machine_rent: u64,
+    pub collection_rent: u64,
+    pub config_line_transactions: usize,
+    pub transactions: u64,
+    pub transaction_fees: u64,
+    pub priority_fees: u64,
+}
+
+impl DeployCost {
+    pub fn total(&self) -> u64 {
+        self.candy_machine_rent + self.collection_rent + self.transaction_fees + self.priority_fees
+    }
+
+    fn add_transaction(&mut self, signatures: u64, priority_fee: u64, compute_units: u32) {
+        self.transactions += 1;
+        self.transaction_fees += signatures * LAMPORTS_PER_SIGNATURE;
+        self.priority_fees += priority_fee_lamports(priority_fee, compute_units);
+    }
+}
+
+/// Estimates the cost of a deploy. The candy machine data is only specified when
+/// the candy machine needs to be created.
+pub fn estimate_deploy_cost<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    candy_machine_data: Option<&CandyMachineData>,
+    create_collection: bool,
+    config_lines: &[Vec<(u32, ConfigLine)>],
+    priority_fee: u64,
+    packed: bool,
+) -> Result<DeployCost> {
+    let rpc_client = program.rpc();
+    let mut cost = DeployCost::default();
+
+    if create_collection {
+        // mint, token account, metadata and master edition accounts
+        for size in [
+            MINT_LAYOUT as usize,
+            SplAccount::LEN,
+            MAX_METADATA_LEN,
+            MAX_MASTER_EDITION_LEN,
+        ] {
+            cost.collection_rent += rpc_client.get_minimum_balance_for_rent_exemption(size)?;
+        }
+        // payer and collection mint
+        cost.add_transaction(2, priority_fee, COMPUTE_UNITS);
+    }
+
+    if let Some(data) = candy_machine_data {
+        let size = data.get_space_for_candy()?;
+        cost.candy_machine_size = Some(size);
+        // candy machine account and collection delegate record
+        cost.candy_machine_rent = rpc_client.get_minimum_balance_for_rent_exemption(size)?
+            + rpc_client.get_minimum_balance_for_rent_exemption(MetadataDelegateRecord::size())?;
+        // payer and candy machine account
+        cost.add_transaction(2, priority_fee, COMPUTE_UNITS);
+    }
+
+    cost.config_line_transactions = config_lines.len();
+
+    for chunk in config_lines {
+        cost.add_transaction(
+            1,
+            priority_fee,
+            config_lines_compute_units(chunk.len(), packed),
+        );
+    }
+
+    Ok(cost)
+}
+
+/// Prints the cost breakdown and checks it against the wallet balance.
+pub fn print_deploy_cost(cost: &DeployCost, balance: u64) {
+    let sol = |lamports: u64| format!("{:.6} SOL", lamports as f64 / LAMPORTS_PER_SOL as f64);
+
+    if let Some(size) = cost.candy_machine_size {
+        println!(
+            "{} {} ({} bytes)",
+            style("Candy machine rent:").bold(),
+            sol(cost.candy_machine_rent),
+            size
+        );
+    }
+
+    if cost.collection_rent > 0 {
+        println!(
+            "{} {}",
+            style("Collection NFT rent:").bold(),
+            sol(cost.collection_rent)
+        );
+    }
+
+    println!(
+        "{} {}",
+        style("Config line transactions:").bold(),
+        cost.config_line_transactions
+    );
+    println!(
+        "{} {} ({} transactions)",
+        style("Transaction fees:").bold(),
+        sol(cost.transaction_fees),
+        cost.transactions
+    );
+    println!(
+        "{} {}",
+        style("Priority fees:").bold(),
+        sol(cost.priority_fees)
+    );
+    println!("{} {}", style("Total:").bold(), sol(cost.total()));
+
+    let balance_message = format!("{} {}", style("Wallet balance:").bold(), sol(balance));
+
+    if balance < cost.total() {
+        println!(
+            "{} {}",
+            balance_message,
+            style("(not enough to deploy)").red().bold()
+        );
+    } else {
+        println!("{}", balance_message);
+    }
+}
+
+/// Priority fee (in lamports) of a transaction, given the price in
+/// micro-lamports per compute unit.
+fn priority_fee_lamports(priority_fee: u64, compute_units: u32) -> u64 {
+    (priority_fee as u128 * compute_units as u128).div_ceil(1_000_000) as u64
+}
+
+#[test]
+fn test_priority_fee_lamports() {
+    assert_eq!(priority_fee_lamports(0, COMPUTE_UNITS), 0);
+    assert_eq!(priority_fee_lamports(500, COMPUTE_UNITS), 200);
+    assert_eq!(priority_fee_lamports(1, 1), 1);
+
+    let mut cost = DeployCost {
+        candy_machine_rent: 1_000,
+        ..Default::default()
+    };
+    cost.add_transaction(2, 500, COMPUTE_UNITS);
+    assert_eq!(cost.total(), 1_000 + 10_000 + 200);
+
+    // unpacked config line transactions use the default limit
+    assert_eq!(
+        priority_fee_lamports(500, config_lines_compute_units(17, false)),
+        100
+    );
+}
@@ -1,11 +1,13 @@
 pub mod collection;
 pub mod config_lines;
+pub mod cost;
 pub mod errors;
 pub mod initialize;
 pub mod process;
 
 pub use collection::*;
 pub use config_lines::*;
+pub use cost::*;
 pub use errors::*;
 pub use initialize::*;
 pub use process::*;
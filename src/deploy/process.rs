@@ -9,6 +9,7 @@ use std::{
 };
 
 use anchor_client::solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
@@ -19,19 +20,23 @@ use mpl_token_metadata::state::Metadata;
 
 use crate::{
     cache::*,
-    candy_machine::{get_candy_machine_state, CANDY_MACHINE_ID},
+    candy_machine::{get_candy_machine_state, load_candy_machine_config_lines, CANDY_MACHINE_ID},
     common::*,
-    config::parser::get_config_data,
+    config::{parser::get_config_data, ConfigData, SugarConfig},
     deploy::{
-        create_candy_machine_data, create_collection, errors::*, generate_config_lines,
-        initialize_candy_machine, upload_config_lines,
+        create_candy_machine_data, create_collection, errors::*, estimate_deploy_cost,
+        generate_config_lines, initialize_candy_machine, print_deploy_cost, upload_config_lines,
     },
     hash::hash_and_update,
     pdas::find_metadata_pda,
-    setup::{setup_client, sugar_setup},
+    setup::{setup_client, sugar_setup, SugarClient},
     update::{process_update, UpdateArgs},
     utils::*,
-    validate::parser::{check_name, check_seller_fee_basis_points, check_symbol, check_url},
+    validate::{
+        parser::{check_name, check_seller_fee_basis_points, check_symbol, check_url},
+        validate_guards,
+    },
+    verify::sync_config_lines,
 };
 
 pub struct DeployArgs {
@@ -42,6 +47,8 @@ pub struct DeployArgs {
     pub interrupted: Arc<AtomicBool>,
     pub collection_mint: Option<String>,
     pub priority_fee: u64,
+    pub pack_config_lines: bool,
+    pub dry_run: bool,
 }
 
 pub async fn process_deploy(args: DeployArgs) -> Result<()> {
@@ -117,6 +124,16 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
     check_symbol(&config_data.symbol)?;
     check_seller_fee_basis_points(config_data.seller_fee_basis_points)?;
 
+    // the guards are added after the deploy, but we check their configuration
+    // before creating any account
+    if let Some(guards) = &config_data.guards {
+        validate_guards(&client.program(CANDY_MACHINE_ID), guards)?;
+    }
+
+    if args.dry_run {
+        return deploy_dry_run(&client, &sugar_config, &cache, &config_data, &args);
+    }
+
     let total_steps = 2 + if candy_machine_address.is_empty() {
         collection_in_cache as u8
     } else {
@@ -253,6 +270,9 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
 
     println!("{} {}", style("Candy machine ID:").bold(), candy_pubkey);
 
+    set_output("candyMachine", candy_pubkey.to_string());
+    set_output("collectionMint", &cache.program.collection_mint);
+
     // Hidden Settings check needs to be the last action in this command, so we can
     // update the hash with the final cache state.
     if !hidden {
@@ -267,10 +287,32 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
             PAPER_EMOJI
         );
 
-        let cndy_state = get_candy_machine_state(&sugar_config, &candy_pubkey)?;
+        let pb = spinner_with_style();
+        pb.set_message("Reading config lines on-chain...");
+
+        // the on-chain config lines are the source of truth of what is
+        // deployed, so only missing or mismatched lines are sent
+        let (cndy_state, on_chain_lines) =
+            load_candy_machine_config_lines(&sugar_config, &candy_pubkey)?;
         let cndy_data = cndy_state.data;
+        let status = sync_config_lines(&mut cache.items, &on_chain_lines);
+
+        if status.changed {
+            cache.sync_file()?;
+        }
 
-        let config_lines = generate_config_lines(num_items, &cache.items, &cndy_data)?;
+        pb.finish_and_clear();
+
+        if status.matched > 0 {
+            println!(
+                "Found {} of {} config line(s) on-chain",
+                status.matched,
+                status.matched + status.errors.len()
+            );
+        }
+
+        let config_lines =
+            generate_config_lines(num_items, &cache.items, &cndy_data, args.pack_config_lines)?;
 
         if config_lines.is_empty() {
             println!("\nAll config lines deployed.");
@@ -285,6 +327,7 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
                 config_lines,
                 args.interrupted,
                 args.priority_fee,
+                args.pack_config_lines,
             )
             .await?;
 
@@ -329,6 +372,8 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
             config: args.config,
             candy_machine: Some(candy_pubkey.to_string()),
             priority_fee: args.priority_fee,
+            export_tx: None,
+            nonce: None,
         };
 
         process_update(update_args)?;
@@ -336,3 +381,86 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Prints the estimated cost of the deploy without sending any transaction.
+fn deploy_dry_run(
+    client: &SugarClient,
+    sugar_config: &SugarConfig,
+    cache: &Cache,
+    config_data: &ConfigData,
+    args: &DeployArgs,
+) -> Result<()> {
+    println!(
+        "{} {}Estimating deploy cost (dry run)",
+        style("[1/1]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let program = client.program(CANDY_MACHINE_ID);
+    let num_items = config_data.number;
+    let new_candy_machine = cache.program.candy_machine.is_empty();
+
+    let (candy_machine_data, create_collection, on_chain_lines) = if new_candy_machine {
+        let create_collection = args.collection_mint.is_none()
+            && cache.items.get("-1").is_some_and(|item| !item.on_chain);
+
+        (
+            create_candy_machine_data(client, config_data, cache)?,
+            create_collection,
+            None,
+        )
+    } else {
+        let candy_pubkey = Pubkey::from_str(&cache.program.candy_machine).map_err(|_| {
+            CacheError::InvalidCandyMachineAddress(cache.program.candy_machine.clone())
+        })?;
+        let (candy_machine_state, on_chain_lines) =
+            load_candy_machine_config_lines(sugar_config, &candy_pubkey)?;
+        (candy_machine_state.data, false, Some(on_chain_lines))
+    };
+
+    let config_lines = if config_data.hidden_settings.is_some() {
+        Vec::new()
+    } else {
+        // only the config lines missing or mismatched on-chain would be sent
+        let mut cache_items = CacheItems(cache.items.0.clone());
+
+        if let Some(on_chain_lines) = on_chain_lines {
+            sync_config_lines(&mut cache_items, &on_chain_lines);
+        }
+
+        generate_config_lines(
+            num_items,
+            &cache_items,
+            &candy_machine_data,
+            args.pack_config_lines,
+        )?
+    };
+
+    let cost = estimate_deploy_cost(
+        &program,
+        new_candy_machine.then_some(&candy_machine_data),
+        create_collection,
+        &config_lines,
+        args.priority_fee,
+        args.pack_config_lines,
+    )?;
+    let balance = program.rpc().get_balance(&program.payer())?;
+
+    pb.finish_and_clear();
+    print_deploy_cost(&cost, balance);
+
+    if balance < cost.total() {
+        return Err(DeployError::BalanceTooLow(
+            format!("{:.3}", balance as f64 / LAMPORTS_PER_SOL as f64),
+            format!("{:.3}", cost.total() as f64 / LAMPORTS_PER_SOL as f64),
+        )
+        .into());
+    }
+
+    println!("\nNo transactions were sent.");
+
+    Ok(())
+}
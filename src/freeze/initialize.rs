@@ -91,6 +91,7 @@ pub fn process_initialize(args: InitializeArgs) -> Result<()> {
     )?;
 
     pb.finish_with_message(format!("{} {}", style("Signature:").bold(), signature));
+    push_output("signatures", signature.to_string());
 
     Ok(())
 }
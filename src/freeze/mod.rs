@@ -21,11 +21,10 @@ use crate::{
     cache::load_cache,
     common::*,
     config::{get_config_data, Cluster, ConfigData, SugarConfig},
+    mints::{get_candy_machine_mints, MintDiscovery},
     pdas::*,
     setup::get_rpc_url,
-    utils::{
-        get_cluster, get_cm_creator_mint_accounts, progress_bar_with_style, spinner_with_style,
-    },
+    utils::{get_cluster, progress_bar_with_style, spinner_with_style},
 };
 
 mod initialize;
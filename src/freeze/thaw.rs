@@ -31,6 +31,7 @@ pub struct ThawArgs {
     pub timeout: Option<u64>,
     pub token: bool,
     pub priority_fee: u64,
+    pub discovery: MintDiscovery,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -306,6 +307,7 @@ pub async fn process_thaw(args: ThawArgs) -> Result<()> {
             style("Thaw NFT signature:").bold(),
             signature
         ));
+        push_output("signatures", signature.to_string());
         return Ok(());
     }
 
@@ -322,14 +324,14 @@ pub async fn process_thaw(args: ThawArgs) -> Result<()> {
 
     let solana_cluster: Cluster = get_cluster(program.rpc())?;
     let rpc_url = get_rpc_url(args.rpc_url);
-    let client = RpcClient::new_with_timeout(
+    let client = Arc::new(RpcClient::new_with_timeout(
         &rpc_url,
         Duration::from_secs(if let Some(timeout) = args.timeout {
             timeout
         } else {
             DEFAULT_TIMEOUT
         }),
-    );
+    ));
 
     let solana_cluster = if rpc_url.ends_with("8899") {
         Cluster::Localnet
@@ -351,18 +353,14 @@ pub async fn process_thaw(args: ThawArgs) -> Result<()> {
                 })
                 .collect()
         } else {
-            match solana_cluster {
-                Cluster::Devnet | Cluster::Localnet | Cluster::Mainnet => {
-                    let (creator, _) = find_candy_machine_creator_pda(&candy_machine);
-                    let creator = bs58::encode(creator).into_string();
-                    get_cm_creator_mint_accounts(&client, &creator, 0)?
-                }
-                _ => {
-                    return Err(anyhow!(
-                        "Cluster being used is unsupported for this command."
-                    ))
-                }
-            }
+            get_candy_machine_mints(
+                client.clone(),
+                &candy_machine,
+                &solana_cluster,
+                args.discovery,
+                &args.cache,
+            )
+            .await?
         };
 
     if mint_pubkeys.is_empty() {
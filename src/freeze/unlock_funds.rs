@@ -5,6 +5,7 @@ use mpl_candy_guard::{
 };
 
 use super::*;
+use crate::tx::{get_tx_authority, send_or_export, TxExport};
 
 pub struct UnlockFundsArgs {
     pub keypair: Option<String>,
@@ -17,6 +18,8 @@ pub struct UnlockFundsArgs {
     pub label: Option<String>,
     pub token: bool,
     pub priority_fee: u64,
+    pub export_tx: Option<TxExport>,
+    pub nonce: Option<Pubkey>,
 }
 
 pub fn process_unlock_funds(args: UnlockFundsArgs) -> Result<()> {
@@ -113,7 +116,11 @@ pub fn process_unlock_funds(args: UnlockFundsArgs) -> Result<()> {
     );
 
     let pb = spinner_with_style();
-    pb.set_message("Sending unlock funds transaction...");
+    pb.set_message(if args.export_tx.is_some() {
+        "Exporting unlock funds transaction..."
+    } else {
+        "Sending unlock funds transaction..."
+    });
 
     let signature = unlock_funds(
         &program,
@@ -123,17 +130,25 @@ pub fn process_unlock_funds(args: UnlockFundsArgs) -> Result<()> {
         &args.label,
         freeze_guard,
         &args.priority_fee,
+        &sugar_config.keypair,
+        &args.export_tx,
+        &args.nonce,
     )?;
 
-    pb.finish_with_message(format!(
-        "{} {}",
-        style("Unlock funds signature:").bold(),
-        signature
-    ));
+    if let Some(signature) = signature {
+        pb.finish_with_message(format!(
+            "{} {}",
+            style("Unlock funds signature:").bold(),
+            signature
+        ));
+    } else {
+        pb.finish_with_message("Unlock funds transaction exported");
+    }
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn unlock_funds<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     candy_guard_id: &Pubkey,
@@ -142,7 +157,11 @@ pub fn unlock_funds<C: Deref<Target = impl Signer> + Clone>(
     label: &Option<String>,
     freeze_guard: GuardType,
     priority_fee: &u64,
-) -> Result<Signature> {
+    signer: &dyn Signer,
+    export_tx: &Option<TxExport>,
+    nonce: &Option<Pubkey>,
+) -> Result<Option<Signature>> {
+    let authority = get_tx_authority(export_tx, program.payer());
     let mut remaining_accounts = Vec::with_capacity(4);
     let (freeze_pda, _) = find_freeze_pda(candy_guard_id, candy_machine_id, destination);
     remaining_accounts.push(AccountMeta {
@@ -151,7 +170,7 @@ pub fn unlock_funds<C: Deref<Target = impl Signer> + Clone>(
         is_writable: true,
     });
     remaining_accounts.push(AccountMeta {
-        pubkey: program.payer(),
+        pubkey: authority,
         is_signer: true,
         is_writable: false,
     });
@@ -209,7 +228,7 @@ pub fn unlock_funds<C: Deref<Target = impl Signer> + Clone>(
         .accounts(RouteAccount {
             candy_guard: *candy_guard_id,
             candy_machine: *candy_machine_id,
-            payer: program.payer(),
+            payer: authority,
         })
         .accounts(remaining_accounts)
         .args(Route {
@@ -219,7 +238,14 @@ pub fn unlock_funds<C: Deref<Target = impl Signer> + Clone>(
             },
             label: label.to_owned(),
         });
-    let sig = builder.send()?;
 
-    Ok(sig)
+    send_or_export(
+        &program.rpc(),
+        &builder.instructions()?,
+        signer,
+        &authority,
+        export_tx,
+        nonce,
+        "Unlock freeze escrow funds",
+    )
 }
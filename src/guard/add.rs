@@ -9,7 +9,10 @@ use mpl_candy_guard::{
 };
 use mpl_candy_machine_core::constants::EMPTY_STR;
 
-use crate::{cache::load_cache, candy_machine::*, common::*, config::get_config_data, utils::*};
+use crate::{
+    cache::load_cache, candy_machine::*, common::*, config::get_config_data, utils::*,
+    validate::validate_guards,
+};
 
 pub struct GuardAddArgs {
     pub keypair: Option<String>,
@@ -68,11 +71,17 @@ pub fn process_guard_add(args: GuardAddArgs) -> Result<()> {
     };
 
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
-    let config_data = get_config_data(&args.config)?;
+    let mut config_data = get_config_data(&args.config)?;
     let client = setup_client(&sugar_config)?;
     let payer = sugar_config.keypair;
     let program = client.program(mpl_candy_guard::ID);
 
+    // checks the guards configuration before sending any transaction
+    if let Some(guards) = &mut config_data.guards {
+        validate_guards(&program, guards)?;
+        resolve_token_amounts(&program, guards)?;
+    }
+
     let candy_guard = if candy_guard_id.is_empty() {
         println!("\n[2/3] {GUARD_EMOJI}Initializing a candy guard");
         let pb = spinner_with_style();
@@ -114,6 +123,7 @@ pub fn process_guard_add(args: GuardAddArgs) -> Result<()> {
 
         pb.finish_and_clear();
         println!("{} {}", style("Signature:").bold(), sig);
+        push_output("signatures", sig.to_string());
 
         candy_guard
     } else {
@@ -167,6 +177,7 @@ pub fn process_guard_add(args: GuardAddArgs) -> Result<()> {
     };
 
     println!("\n{} {}", style("Candy guard ID:").bold(), candy_guard);
+    set_output("candyGuard", candy_guard.to_string());
 
     // wraps the candy machine
 
@@ -193,6 +204,7 @@ pub fn process_guard_add(args: GuardAddArgs) -> Result<()> {
 
     pb.finish_and_clear();
     println!("{} {}", style("Signature:").bold(), sig);
+    push_output("signatures", sig.to_string());
 
     println!("\nThe candy guard is now the mint authority of the candy machine.");
 
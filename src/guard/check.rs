@@ -0,0 +1,634 @@
+use std::{collections::HashSet, str::FromStr};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chrono::{NaiveDateTime, Utc};
+use console::style;
+use mpl_candy_guard::{
+    guards::GuardSet,
+    state::{CandyGuardData, DATA_OFFSET},
+};
+use mpl_token_metadata::state::Metadata;
+use solana_account_decoder::UiAccountData;
+use solana_client::{rpc_client::RpcClient, rpc_request::TokenAccountsFilter};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{
+    cache::load_cache, candy_machine::CANDY_MACHINE_ID, common::*, config::parse_date_as_timestamp,
+    pdas::*, program_errors::CANDY_GUARD_ERROR, utils::*,
+};
+
+/// Extra lamports charged by the freeze sol payment guard. This mirrors
+/// `guards::freeze_sol_payment::FREEZE_SOL_FEE` of mpl-candy-guard, which is not
+/// exported by the crate (its module is private), so it must be checked on
+/// every mpl-candy-guard upgrade.
+const FREEZE_SOL_FEE: u64 = 10_000;
+
+pub struct GuardCheckArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub candy_machine: Option<String>,
+    pub candy_guard: Option<String>,
+    pub wallet: String,
+    pub group: Option<String>,
+    pub at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The guard condition is met.
+    Pass,
+    /// The guard condition is not met; the mint would fail.
+    Fail,
+    /// The guard condition cannot be evaluated from chain state alone.
+    Manual,
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardCheck {
+    pub guard: &'static str,
+    pub status: CheckStatus,
+    /// Candy guard error code (hex) that the mint would fail with.
+    pub code: Option<&'static str>,
+    pub detail: String,
+}
+
+impl GuardCheck {
+    fn pass(guard: &'static str, detail: String) -> Self {
+        Self {
+            guard,
+            status: CheckStatus::Pass,
+            code: None,
+            detail,
+        }
+    }
+
+    fn fail(guard: &'static str, code: &'static str, detail: String) -> Self {
+        Self {
+            guard,
+            status: CheckStatus::Fail,
+            code: Some(code),
+            detail,
+        }
+    }
+
+    fn manual(guard: &'static str, detail: String) -> Self {
+        Self {
+            guard,
+            status: CheckStatus::Manual,
+            code: None,
+            detail,
+        }
+    }
+
+    fn check(guard: &'static str, passed: bool, code: &'static str, detail: String) -> Self {
+        if passed {
+            Self::pass(guard, detail)
+        } else {
+            Self::fail(guard, code, detail)
+        }
+    }
+}
+
+pub fn process_guard_check(args: GuardCheckArgs) -> Result<()> {
+    println!(
+        "{} {}Loading candy guard",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    // the candy guard and candy machine ids specified take precedence over the
+    // ones from the cache
+
+    let candy_guard_id = match args.candy_guard {
+        Some(ref candy_guard_id) => candy_guard_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_guard
+        }
+    };
+
+    let candy_machine_id = match args.candy_machine {
+        Some(ref candy_machine_id) => candy_machine_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_machine
+        }
+    };
+
+    if candy_guard_id.is_empty() {
+        return Err(anyhow!("Missing candy guard id."));
+    }
+
+    if candy_machine_id.is_empty() {
+        return Err(anyhow!("Missing candy machine id."));
+    }
+
+    let candy_guard = Pubkey::from_str(&candy_guard_id)
+        .map_err(|_| anyhow!("Failed to parse candy guard id: {}", &candy_guard_id))?;
+
+    let candy_machine = Pubkey::from_str(&candy_machine_id)
+        .map_err(|_| anyhow!("Failed to parse candy machine id: {}", &candy_machine_id))?;
+
+    let wallet = Pubkey::from_str(&args.wallet)
+        .map_err(|_| anyhow!("Failed to parse wallet address: {}", &args.wallet))?;
+
+    let timestamp = match &args.at {
+        Some(at) => {
+            parse_date_as_timestamp(at, &[]).map_err(|_| anyhow!("Failed to parse date: {}", at))?
+        }
+        None => Utc::now().timestamp(),
+    };
+
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let account_data = program.rpc().get_account_data(&candy_guard)?;
+    // resolves the guard set (default guards merged with the group guards)
+    let guard_set = CandyGuardData::active_set(&account_data[DATA_OFFSET..], args.group.clone())
+        .map_err(|_| match &args.group {
+            Some(label) => anyhow!("Could not find group with label '{label}'"),
+            None => anyhow!("Missing group label: candy guard has groups"),
+        })?;
+    let candy_machine_state: CandyMachine = program.account(candy_machine)?;
+
+    pb.finish_with_message("Done");
+
+    println!("{} {}", style("Candy guard ID:").bold(), candy_guard);
+    println!("{} {}", style("Wallet:").bold(), wallet);
+    if let Some(date) = NaiveDateTime::from_timestamp_opt(timestamp, 0) {
+        println!(
+            "{} {}",
+            style("Time:").bold(),
+            date.format("%a %B %e %Y %H:%M:%S UTC")
+        );
+    }
+
+    println!(
+        "\n{} {}Checking guards",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Evaluating guards...");
+
+    let context = CheckContext {
+        rpc_client: program.rpc(),
+        candy_guard,
+        candy_machine,
+        candy_machine_state,
+        wallet,
+        timestamp,
+    };
+    let checks = check_guard_set(&context, &guard_set)?;
+
+    pb.finish_and_clear();
+
+    let mut failed = 0;
+
+    for check in &checks {
+        let (marker, guard) = match check.status {
+            CheckStatus::Pass => (style("✔").green(), style(check.guard).green()),
+            CheckStatus::Fail => {
+                failed += 1;
+                (style("✖").red(), style(check.guard).red())
+            }
+            CheckStatus::Manual => (style("?").yellow(), style(check.guard).yellow()),
+        };
+
+        println!("{marker} {guard}: {}", check.detail);
+
+        if let Some(code) = check.code {
+            println!(
+                "    {} {}",
+                style(format!("0x{code}")).dim(),
+                CANDY_GUARD_ERROR.get(code).unwrap_or(&"Unknown error")
+            );
+        }
+    }
+
+    if failed > 0 {
+        println!(
+            "\n{}{}",
+            WARNING_EMOJI,
+            style(format!("{failed} guard(s) would prevent the mint.")).bold()
+        );
+    } else {
+        println!(
+            "\n{}{}",
+            CONFETTI_EMOJI,
+            style("The wallet is eligible to mint.").bold()
+        );
+    }
+
+    Ok(())
+}
+
+pub struct CheckContext {
+    pub rpc_client: RpcClient,
+    pub candy_guard: Pubkey,
+    pub candy_machine: Pubkey,
+    pub candy_machine_state: CandyMachine,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Evaluates each guard of the (resolved) guard set against the chain state.
+pub fn check_guard_set(context: &CheckContext, guard_set: &GuardSet) -> Result<Vec<GuardCheck>> {
+    let mut checks = Vec::new();
+    let rpc_client = &context.rpc_client;
+    let wallet = &context.wallet;
+    let items_redeemed = context.candy_machine_state.items_redeemed;
+    let items_available = context.candy_machine_state.data.items_available;
+
+    checks.push(GuardCheck::check(
+        "candy machine",
+        items_redeemed < items_available,
+        "177B",
+        format!("{items_redeemed} of {items_available} items redeemed"),
+    ));
+
+    if let Some(bot_tax) = &guard_set.bot_tax {
+        checks.push(GuardCheck::pass(
+            "bot tax",
+            format!(
+                "{} lamports charged on invalid transactions",
+                bot_tax.lamports
+            ),
+        ));
+    }
+
+    if let Some(start_date) = &guard_set.start_date {
+        checks.push(GuardCheck::check(
+            "start date",
+            context.timestamp >= start_date.date,
+            "1781",
+            format!("starts at {}", format_timestamp(start_date.date)),
+        ));
+    }
+
+    if let Some(end_date) = &guard_set.end_date {
+        checks.push(GuardCheck::check(
+            "end date",
+            context.timestamp < end_date.date,
+            "1788",
+            format!("ends at {}", format_timestamp(end_date.date)),
+        ));
+    }
+
+    // the sol payments and freeze fees are all charged on the same mint, so
+    // the wallet must hold their sum
+    let mut required_lamports = 0;
+    let mut sol_charges = Vec::new();
+
+    if let Some(sol_payment) = &guard_set.sol_payment {
+        required_lamports += sol_payment.lamports;
+        sol_charges.push(format!("{} sol payment", sol_payment.lamports));
+    }
+
+    if let Some(freeze_sol_payment) = &guard_set.freeze_sol_payment {
+        required_lamports += freeze_sol_payment.lamports + FREEZE_SOL_FEE;
+        sol_charges.push(format!(
+            "{} freeze sol payment + {FREEZE_SOL_FEE} freeze fee",
+            freeze_sol_payment.lamports
+        ));
+    }
+
+    if guard_set.freeze_token_payment.is_some() {
+        required_lamports += FREEZE_SOL_FEE;
+        sol_charges.push(format!("{FREEZE_SOL_FEE} freeze fee"));
+    }
+
+    // sol balance is only retrieved if needed
+    if !sol_charges.is_empty() {
+        let balance = rpc_client.get_balance(wallet)?;
+
+        checks.push(GuardCheck::check(
+            "sol payment",
+            balance >= required_lamports,
+            "1782",
+            format!(
+                "requires {required_lamports} lamports ({}), wallet has {balance}",
+                sol_charges.join(" + ")
+            ),
+        ));
+    }
+
+    if let Some(token_payment) = &guard_set.token_payment {
+        checks.push(check_token_balance(
+            rpc_client,
+            "token payment",
+            wallet,
+            &token_payment.mint,
+            &spl_token::ID,
+            token_payment.amount,
+        )?);
+    }
+
+    if let Some(freeze_token_payment) = &guard_set.freeze_token_payment {
+        checks.push(check_token_balance(
+            rpc_client,
+            "freeze token payment",
+            wallet,
+            &freeze_token_payment.mint,
+            &spl_token::ID,
+            freeze_token_payment.amount,
+        )?);
+    }
+
+    if let Some(token2022_payment) = &guard_set.token2022_payment {
+        checks.push(check_token_balance(
+            rpc_client,
+            "token2022 payment",
+            wallet,
+            &token2022_payment.mint,
+            &Pubkey::from_str(TOKEN_2022_PROGRAM_ID)?,
+            token2022_payment.amount,
+        )?);
+    }
+
+    if let Some(token_burn) = &guard_set.token_burn {
+        checks.push(check_token_balance(
+            rpc_client,
+            "token burn",
+            wallet,
+            &token_burn.mint,
+            &spl_token::ID,
+            token_burn.amount,
+        )?);
+    }
+
+    if let Some(token_gate) = &guard_set.token_gate {
+        checks.push(check_token_balance(
+            rpc_client,
+            "token gate",
+            wallet,
+            &token_gate.mint,
+            &spl_token::ID,
+            token_gate.amount,
+        )?);
+    }
+
+    // collections of the NFTs held by the wallet are only retrieved if needed
+    let collections = if guard_set.nft_gate.is_some()
+        || guard_set.nft_burn.is_some()
+        || guard_set.nft_payment.is_some()
+    {
+        get_held_collections(rpc_client, wallet)?
+    } else {
+        HashSet::new()
+    };
+
+    if let Some(nft_gate) = &guard_set.nft_gate {
+        checks.push(check_collection(
+            "nft gate",
+            &collections,
+            &nft_gate.required_collection,
+        ));
+    }
+
+    if let Some(nft_burn) = &guard_set.nft_burn {
+        checks.push(check_collection(
+            "nft burn",
+            &collections,
+            &nft_burn.required_collection,
+        ));
+    }
+
+    if let Some(nft_payment) = &guard_set.nft_payment {
+        checks.push(check_collection(
+            "nft payment",
+            &collections,
+            &nft_payment.required_collection,
+        ));
+    }
+
+    if let Some(mint_limit) = &guard_set.mint_limit {
+        let count = get_mint_counter(
+            rpc_client,
+            mint_limit.id,
+            wallet,
+            &context.candy_guard,
+            &context.candy_machine,
+        )?
+        .unwrap_or(0);
+
+        checks.push(GuardCheck::check(
+            "mint limit",
+            count < mint_limit.limit,
+            "178D",
+            format!(
+                "{} of {} mints used (id {})",
+                count, mint_limit.limit, mint_limit.id
+            ),
+        ));
+    }
+
+    if let Some(redeemed_amount) = &guard_set.redeemed_amount {
+        checks.push(GuardCheck::check(
+            "redeemed amount",
+            items_redeemed < redeemed_amount.maximum,
+            "1790",
+            format!(
+                "{} of {} items redeemed",
+                items_redeemed, redeemed_amount.maximum
+            ),
+        ));
+    }
+
+    if let Some(address_gate) = &guard_set.address_gate {
+        checks.push(GuardCheck::check(
+            "address gate",
+            address_gate.address == *wallet,
+            "1791",
+            format!("only {} can mint", address_gate.address),
+        ));
+    }
+
+    if let Some(allocation) = &guard_set.allocation {
+        checks.push(
+            match get_allocation_count(
+                rpc_client,
+                allocation.id,
+                &context.candy_guard,
+                &context.candy_machine,
+            )? {
+                Some(count) => GuardCheck::check(
+                    "allocation",
+                    count < allocation.limit,
+                    "179F",
+                    format!(
+                        "{} of {} mints allocated (id {})",
+                        count, allocation.limit, allocation.id
+                    ),
+                ),
+                None => GuardCheck::fail(
+                    "allocation",
+                    "179E",
+                    format!("tracker for id {} not initialized", allocation.id),
+                ),
+            },
+        );
+    }
+
+    if let Some(allow_list) = &guard_set.allow_list {
+        let (proof_pda, _) = find_allow_list_proof_pda(
+            &allow_list.merkle_root,
+            wallet,
+            &context.candy_guard,
+            &context.candy_machine,
+        );
+        let validated = rpc_client
+            .get_account_with_commitment(&proof_pda, CommitmentConfig::confirmed())?
+            .value
+            .is_some();
+
+        checks.push(GuardCheck::check(
+            "allow list",
+            validated,
+            "178B",
+            if validated {
+                "merkle proof validated".to_string()
+            } else {
+                "merkle proof not validated yet for the wallet".to_string()
+            },
+        ));
+    }
+
+    if let Some(third_party_signer) = &guard_set.third_party_signer {
+        checks.push(GuardCheck::manual(
+            "third party signer",
+            format!(
+                "requires a signature from {}",
+                third_party_signer.signer_key
+            ),
+        ));
+    }
+
+    if let Some(gatekeeper) = &guard_set.gatekeeper {
+        checks.push(GuardCheck::manual(
+            "gatekeeper",
+            format!(
+                "requires a valid gateway token from network {}",
+                gatekeeper.gatekeeper_network
+            ),
+        ));
+    }
+
+    if let Some(program_gate) = &guard_set.program_gate {
+        checks.push(GuardCheck::pass(
+            "program gate",
+            format!(
+                "{} additional program(s) allowed in the transaction",
+                program_gate.additional.len()
+            ),
+        ));
+    }
+
+    Ok(checks)
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    if let Some(date) = NaiveDateTime::from_timestamp_opt(timestamp, 0) {
+        date.format("%a %B %e %Y %H:%M:%S UTC").to_string()
+    } else {
+        "<parse error>".to_string()
+    }
+}
+
+fn check_token_balance(
+    rpc_client: &RpcClient,
+    guard: &'static str,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Result<GuardCheck> {
+    let ata = get_associated_token_address_with_program_id(wallet, mint, token_program);
+
+    // missing token account means no balance
+    let balance = if rpc_client
+        .get_account_with_commitment(&ata, rpc_client.commitment())?
+        .value
+        .is_some()
+    {
+        let balance = rpc_client.get_token_account_balance(&ata)?;
+        balance
+            .amount
+            .parse::<u64>()
+            .map_err(|err| anyhow!("Invalid balance of token account {ata}: {err}"))?
+    } else {
+        0
+    };
+
+    Ok(GuardCheck::check(
+        guard,
+        balance >= amount,
+        "1784",
+        format!("requires {amount} of {mint}, wallet has {balance}"),
+    ))
+}
+
+fn check_collection(
+    guard: &'static str,
+    collections: &HashSet<Pubkey>,
+    required_collection: &Pubkey,
+) -> GuardCheck {
+    GuardCheck::check(
+        guard,
+        collections.contains(required_collection),
+        "178F",
+        format!("requires an NFT from collection {required_collection}"),
+    )
+}
+
+/// Returns the (verified) collections of the NFTs held by the wallet.
+fn get_held_collections(rpc_client: &RpcClient, wallet: &Pubkey) -> Result<HashSet<Pubkey>> {
+    let accounts = rpc_client
+        .get_token_accounts_by_owner(wallet, TokenAccountsFilter::ProgramId(spl_token::ID))?;
+
+    let mut mints = Vec::new();
+
+    for keyed_account in accounts {
+        if let UiAccountData::Json(parsed) = keyed_account.account.data {
+            let info = &parsed.parsed["info"];
+            let amount = info["tokenAmount"]["amount"].as_str().unwrap_or("0");
+            let decimals = info["tokenAmount"]["decimals"].as_u64().unwrap_or(0);
+
+            if decimals == 0 && amount != "0" {
+                if let Some(mint) = info["mint"].as_str() {
+                    mints.push(Pubkey::from_str(mint)?);
+                }
+            }
+        }
+    }
+
+    let metadata_pdas: Vec<Pubkey> = mints.iter().map(find_metadata_pda).collect();
+    let mut collections = HashSet::new();
+
+    // retrieves the metadata accounts in batches
+    for chunk in metadata_pdas.chunks(100) {
+        for account in rpc_client
+            .get_multiple_accounts(chunk)?
+            .into_iter()
+            .flatten()
+        {
+            if let Ok(metadata) = Metadata::deserialize(&mut account.data.as_slice()) {
+                if let Some(collection) = metadata.collection {
+                    if collection.verified {
+                        collections.insert(collection.key);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(collections)
+}
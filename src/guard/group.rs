@@ -0,0 +1,354 @@
+use anyhow::Result;
+use console::style;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    common::*,
+    config::{get_config_value, save_config_value, CandyGuardData, GuardSet},
+    guard::{process_guard_update, GuardUpdateArgs},
+    validate::{lint_guards, report_guard_lints},
+};
+
+pub enum GroupAction {
+    /// Add a new group, optionally copying the guards of an existing group.
+    Add { label: String, from: Option<String> },
+    /// Remove an existing group.
+    Remove { label: String },
+    /// Rename an existing group.
+    Rename { label: String, new_label: String },
+    /// Set a guard (or guard field) value on an existing group.
+    Set {
+        label: String,
+        field: String,
+        value: String,
+    },
+}
+
+pub struct GuardGroupArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub config: String,
+    pub candy_guard: Option<String>,
+    pub priority_fee: u64,
+    pub update: bool,
+    pub action: GroupAction,
+}
+
+pub fn process_guard_group(args: GuardGroupArgs) -> Result<()> {
+    let total_steps = if args.update { 2 } else { 1 };
+
+    println!(
+        "{} {}Updating guard groups in config file",
+        style(format!("[1/{total_steps}]")).bold().dim(),
+        PAPER_EMOJI
+    );
+
+    // the guards are edited as JSON, so the rest of the config file is unchanged
+    let mut config = get_config_value(&args.config)?;
+    let config_object = config
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Invalid config file '{}'", args.config))?;
+
+    let guards = config_object
+        .entry("guards")
+        .or_insert_with(|| json!({ "default": {} }));
+
+    if guards.is_null() {
+        *guards = json!({ "default": {} });
+    }
+
+    let message = apply_group_action(guards, &args.action)?;
+
+    // makes sure the resulting configuration is valid before saving it
+    let guard_data: CandyGuardData = serde_json::from_value(guards.clone())
+        .map_err(|err| anyhow!("Invalid guards configuration: {err}"))?;
+    report_guard_lints(&lint_guards(&guard_data)?)?;
+
+    save_config_value(&args.config, &config)?;
+
+    println!("{} {}", style("Config file updated:").bold(), message);
+
+    if args.update {
+        println!(
+            "\n{} {}Pushing guards configuration on-chain\n",
+            style(format!("[2/{total_steps}]")).bold().dim(),
+            GUARD_EMOJI
+        );
+
+        process_guard_update(GuardUpdateArgs {
+            keypair: args.keypair,
+            rpc_url: args.rpc_url,
+            cache: args.cache,
+            config: args.config,
+            candy_guard: args.candy_guard,
+            priority_fee: args.priority_fee,
+            export_tx: None,
+            nonce: None,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Applies the action to the guards configuration (as JSON), returning a
+/// description of the change. Only the group of the action is changed.
+pub fn apply_group_action(guards: &mut Value, action: &GroupAction) -> Result<String> {
+    let guards = guards
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Invalid guards configuration"))?;
+
+    let groups = guards
+        .entry("groups")
+        .or_insert_with(|| Value::Array(Vec::new()));
+
+    if groups.is_null() {
+        *groups = Value::Array(Vec::new());
+    }
+
+    let groups = groups
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("Invalid guard groups configuration"))?;
+
+    let message = match action {
+        GroupAction::Add { label, from } => {
+            if groups.iter().any(|group| group_label(group) == Some(label)) {
+                return Err(anyhow!("Group with label '{label}' already exists"));
+            }
+
+            let guard_set = match from {
+                Some(from) => find_group(groups, from)?["guards"].clone(),
+                None => Value::Object(Map::new()),
+            };
+
+            groups.push(json!({
+                "label": label,
+                "guards": guard_set,
+            }));
+
+            format!("added group '{label}'")
+        }
+        GroupAction::Remove { label } => {
+            let index = groups
+                .iter()
+                .position(|group| group_label(group) == Some(label))
+                .ok_or_else(|| anyhow!("Could not find group with label '{label}'"))?;
+            groups.remove(index);
+
+            format!("removed group '{label}'")
+        }
+        GroupAction::Rename { label, new_label } => {
+            if groups
+                .iter()
+                .any(|group| group_label(group) == Some(new_label))
+            {
+                return Err(anyhow!("Group with label '{new_label}' already exists"));
+            }
+
+            find_group(groups, label)?["label"] = Value::String(new_label.to_owned());
+
+            format!("renamed group '{label}' to '{new_label}'")
+        }
+        GroupAction::Set {
+            label,
+            field,
+            value,
+        } => {
+            let group = find_group(groups, label)?;
+
+            if !group["guards"].is_object() {
+                group["guards"] = Value::Object(Map::new());
+            }
+
+            set_guard_field(&mut group["guards"], field, value)?;
+
+            format!("set '{field}' on group '{label}'")
+        }
+    };
+
+    if groups.is_empty() {
+        guards.remove("groups");
+    }
+
+    Ok(message)
+}
+
+fn group_label(group: &Value) -> Option<&str> {
+    group.get("label").and_then(Value::as_str)
+}
+
+fn find_group<'a>(groups: &'a mut [Value], label: &str) -> Result<&'a mut Value> {
+    groups
+        .iter_mut()
+        .find(|group| group_label(group) == Some(label))
+        .ok_or_else(|| anyhow!("Could not find group with label '{label}'"))
+}
+
+/// Sets the value of a guard (e.g., "mintLimit") or guard field (e.g.,
+/// "solPayment.value"). The value is parsed as JSON, falling back to a
+/// string; a `null` value removes the guard (or field).
+fn set_guard_field(guard_set: &mut Value, field: &str, value: &str) -> Result<()> {
+    let keys: Vec<&str> = field.split('.').collect();
+
+    // only known guards can be set
+    let guard = keys[0];
+    if serde_json::to_value(GuardSet::default())?
+        .get(guard)
+        .is_none()
+    {
+        return Err(anyhow!("Unknown guard '{guard}'"));
+    }
+
+    let value =
+        serde_json::from_str::<Value>(value).unwrap_or_else(|_| Value::String(value.to_string()));
+
+    let mut updated = guard_set.clone();
+    let mut current = &mut updated;
+
+    for key in &keys[..keys.len() - 1] {
+        if !current[*key].is_object() {
+            current[*key] = Value::Object(Map::new());
+        }
+        current = &mut current[*key];
+    }
+
+    let last = keys[keys.len() - 1];
+
+    match (value.is_null(), current.as_object_mut()) {
+        (true, Some(object)) => {
+            object.remove(last);
+        }
+        (false, Some(object)) => {
+            object.insert(last.to_string(), value);
+        }
+        _ => return Err(anyhow!("Invalid guard field '{field}'")),
+    }
+
+    serde_json::from_value::<GuardSet>(updated.clone())
+        .map_err(|err| anyhow!("Invalid value for guard field '{field}': {err}"))?;
+
+    *guard_set = updated;
+
+    Ok(())
+}
+
+#[test]
+fn test_apply_group_action() {
+    let mut guards = json!({ "default": { "botTax": { "value": 0.01, "lastInstruction": true } } });
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Add {
+            label: "og".to_string(),
+            from: None,
+        },
+    )
+    .unwrap();
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Set {
+            label: "og".to_string(),
+            field: "mintLimit".to_string(),
+            value: r#"{ "id": 1, "limit": 2 }"#.to_string(),
+        },
+    )
+    .unwrap();
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Set {
+            label: "og".to_string(),
+            field: "mintLimit.limit".to_string(),
+            value: "5".to_string(),
+        },
+    )
+    .unwrap();
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Add {
+            label: "public".to_string(),
+            from: Some("og".to_string()),
+        },
+    )
+    .unwrap();
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Rename {
+            label: "og".to_string(),
+            new_label: "wl".to_string(),
+        },
+    )
+    .unwrap();
+
+    let guard_data: CandyGuardData = serde_json::from_value(guards.clone()).unwrap();
+    let groups = guard_data.groups.as_ref().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].label, "wl");
+    assert_eq!(groups[1].guards.mint_limit.as_ref().unwrap().limit, 5);
+
+    // unknown guards, invalid values and duplicated labels are rejected
+    assert!(apply_group_action(
+        &mut guards,
+        &GroupAction::Set {
+            label: "wl".to_string(),
+            field: "unknownGuard".to_string(),
+            value: "1".to_string(),
+        },
+    )
+    .is_err());
+    assert!(apply_group_action(
+        &mut guards,
+        &GroupAction::Set {
+            label: "wl".to_string(),
+            field: "mintLimit.limit".to_string(),
+            value: "many".to_string(),
+        },
+    )
+    .is_err());
+    assert!(apply_group_action(
+        &mut guards,
+        &GroupAction::Rename {
+            label: "wl".to_string(),
+            new_label: "public".to_string(),
+        },
+    )
+    .is_err());
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Set {
+            label: "wl".to_string(),
+            field: "mintLimit".to_string(),
+            value: "null".to_string(),
+        },
+    )
+    .unwrap();
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Remove {
+            label: "public".to_string(),
+        },
+    )
+    .unwrap();
+
+    // the default guards are left as they were and unset guards are not added
+    assert_eq!(
+        guards,
+        json!({
+            "default": { "botTax": { "value": 0.01, "lastInstruction": true } },
+            "groups": [{ "label": "wl", "guards": {} }]
+        })
+    );
+
+    apply_group_action(
+        &mut guards,
+        &GroupAction::Remove {
+            label: "wl".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(guards.get("groups").is_none());
+}
@@ -1,11 +1,21 @@
 pub mod add;
+pub mod check;
+pub mod group;
+pub mod phases;
 pub mod remove;
+pub mod route;
 pub mod show;
 pub mod update;
+pub mod usage;
 pub mod withdraw;
 
 pub use add::*;
+pub use check::*;
+pub use group::*;
+pub use phases::*;
 pub use remove::*;
+pub use route::*;
 pub use show::*;
 pub use update::*;
+pub use usage::*;
 pub use withdraw::*;
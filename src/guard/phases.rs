@@ -0,0 +1,373 @@
+use std::fs::File;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use console::style;
+use dialoguer::Confirm;
+use mpl_candy_guard::state::MAX_LABEL_SIZE;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    common::*,
+    config::{
+        get_config_data, get_config_value, save_config_value, to_pubkey, to_sol_amount, to_string,
+        to_value_without_nulls, AllowList, EndDate, Group, GuardSet, MintLimit, SolPayment,
+        StartDate, TokenAmount, TokenPayment,
+    },
+    guard::{process_guard_update, GuardUpdateArgs},
+    utils::get_dialoguer_theme,
+    validate::{lint_guards, report_guard_lints, GuardLint},
+};
+
+pub struct GuardPhasesArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub config: String,
+    pub candy_guard: Option<String>,
+    pub priority_fee: u64,
+    pub phases: String,
+    pub update: bool,
+    pub force: bool,
+}
+
+/// Phases file, describing the mint phases of a launch (e.g., OG → allow list
+/// → public, or the steps of a Dutch auction).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhasesData {
+    /// Wallet receiving the payments of all phases.
+    #[serde(deserialize_with = "to_pubkey")]
+    #[serde(serialize_with = "to_string")]
+    pub destination: Pubkey,
+
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Phase {
+    /// Group label, defaults to "phase<n>" (or "ph<n>" from the 10th phase).
+    pub label: Option<String>,
+    pub start: String,
+    /// End of the phase, defaults to the start of the next phase.
+    pub end: Option<String>,
+    /// Price in SOL.
+    #[serde(default, deserialize_with = "to_optional_sol_amount")]
+    pub price: Option<TokenAmount>,
+    /// Price in spl-token (amount in base units, or UI units as a string).
+    pub token_price: Option<TokenPrice>,
+    /// Merkle root (hex) of the allow list.
+    pub allow_list: Option<String>,
+    /// Maximum number of mints per wallet.
+    pub limit: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TokenPrice {
+    pub amount: TokenAmount,
+
+    #[serde(deserialize_with = "to_pubkey")]
+    #[serde(serialize_with = "to_string")]
+    pub mint: Pubkey,
+}
+
+fn to_optional_sol_amount<'de, D>(deserializer: D) -> Result<Option<TokenAmount>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    to_sol_amount(deserializer).map(Some)
+}
+
+pub fn process_guard_phases(args: GuardPhasesArgs) -> Result<()> {
+    let total_steps = if args.update { 2 } else { 1 };
+
+    println!(
+        "{} {}Generating guard groups from phases file",
+        style(format!("[1/{total_steps}]")).bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let file = File::open(&args.phases)
+        .map_err(|err| anyhow!("Failed to open phases file '{}': {err}", args.phases))?;
+    let phases_data: PhasesData = serde_json::from_reader(file)
+        .map_err(|err| anyhow!("Failed to parse phases file '{}': {err}", args.phases))?;
+
+    let (groups, mut lints) = expand_phases(&phases_data)?;
+
+    let mut config = get_config_value(&args.config)?;
+    let mut guards = get_config_data(&args.config)?.guards.unwrap_or_default();
+
+    if guards
+        .groups
+        .as_ref()
+        .is_some_and(|groups| !groups.is_empty())
+    {
+        println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style("Existing guard groups will be replaced").yellow()
+        );
+
+        if !args.force
+            && !Confirm::with_theme(&get_dialoguer_theme())
+                .with_prompt("Do you want to replace the existing guard groups?")
+                .interact()?
+        {
+            return Err(anyhow!("Operation aborted"));
+        }
+    }
+
+    let labels: Vec<String> = groups.iter().map(|group| group.label.clone()).collect();
+    guards.groups = Some(groups);
+
+    // makes sure the resulting configuration is valid before saving it
+    lints.extend(lint_guards(&guards)?);
+    report_guard_lints(&lints)?;
+
+    // only the groups are replaced, the rest of the config file is unchanged
+    match config.get_mut("guards") {
+        Some(Value::Object(guards_value)) => {
+            guards_value.insert(
+                "groups".to_string(),
+                to_value_without_nulls(&guards.groups)?,
+            );
+        }
+        _ => config["guards"] = to_value_without_nulls(&guards)?,
+    }
+    save_config_value(&args.config, &config)?;
+
+    println!(
+        "{} {}",
+        style("Config file updated with groups:").bold(),
+        labels.join(", ")
+    );
+
+    if args.update {
+        println!(
+            "\n{} {}Pushing guards configuration on-chain\n",
+            style(format!("[2/{total_steps}]")).bold().dim(),
+            GUARD_EMOJI
+        );
+
+        process_guard_update(GuardUpdateArgs {
+            keypair: args.keypair,
+            rpc_url: args.rpc_url,
+            cache: args.cache,
+            config: args.config,
+            candy_guard: args.candy_guard,
+            priority_fee: args.priority_fee,
+            export_tx: None,
+            nonce: None,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Expands the phases into guard groups, returning the groups together with
+/// the issues found on the phases schedule (gaps and overlaps).
+pub fn expand_phases(phases_data: &PhasesData) -> Result<(Vec<Group>, Vec<GuardLint>)> {
+    if phases_data.phases.is_empty() {
+        return Err(anyhow!("Phases file does not contain any phase"));
+    }
+
+    let mut lints = Vec::new();
+    let mut schedule = Vec::with_capacity(phases_data.phases.len());
+
+    for (index, phase) in phases_data.phases.iter().enumerate() {
+        let label = phase.label.clone().unwrap_or_else(|| default_label(index));
+
+        if label.is_empty() || label.len() > MAX_LABEL_SIZE {
+            return Err(anyhow!(
+                "Invalid label '{label}': labels must have between 1 and {MAX_LABEL_SIZE} characters"
+            ));
+        }
+
+        if schedule.iter().any(|(other, _, _)| other == &label) {
+            return Err(anyhow!("Duplicated phase label '{label}'"));
+        }
+
+        let start = parse_timestamp(&phase.start, &label)?;
+        let end = match &phase.end {
+            Some(end) => Some(parse_timestamp(end, &label)?),
+            None => None,
+        };
+
+        if end.is_some_and(|end| end <= start) {
+            return Err(anyhow!("Phase '{label}' ends before it starts"));
+        }
+
+        schedule.push((label, start, end));
+    }
+
+    // phases must be listed in chronological order
+    for window in schedule.windows(2) {
+        let (label, start, end) = &window[0];
+        let (next_label, next_start, _) = &window[1];
+
+        if next_start <= start {
+            return Err(anyhow!(
+                "Phase '{next_label}' must start after phase '{label}'"
+            ));
+        }
+
+        match end {
+            Some(end) if end > next_start => lints.push(GuardLint::error(
+                label,
+                format!("phase overlaps with phase '{next_label}'"),
+            )),
+            Some(end) if end < next_start => lints.push(GuardLint::warning(
+                label,
+                format!(
+                    "gap of {} second(s) before phase '{next_label}'",
+                    next_start - end
+                ),
+            )),
+            _ => (),
+        }
+    }
+
+    let mut groups = Vec::with_capacity(schedule.len());
+
+    for (index, phase) in phases_data.phases.iter().enumerate() {
+        let label = &schedule[index].0;
+
+        // phases without an end date run until the next phase starts
+        let end_date = match (&phase.end, phases_data.phases.get(index + 1)) {
+            (Some(end), _) => Some(end.clone()),
+            (None, Some(next)) => Some(next.start.clone()),
+            (None, None) => None,
+        };
+
+        let mut guards = GuardSet {
+            start_date: Some(StartDate {
+                date: phase.start.clone(),
+            }),
+            end_date: end_date.map(|date| EndDate { date }),
+            ..Default::default()
+        };
+
+        match (&phase.price, &phase.token_price) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "Phase '{label}' can only specify one of 'price' and 'tokenPrice'"
+                ))
+            }
+            (Some(price), None) => {
+                guards.sol_payment = Some(SolPayment {
+                    value: price.clone(),
+                    destination: phases_data.destination,
+                });
+            }
+            (None, Some(token_price)) => {
+                guards.token_payment = Some(TokenPayment {
+                    amount: token_price.amount.clone(),
+                    mint: token_price.mint,
+                    destination_ata: get_associated_token_address(
+                        &phases_data.destination,
+                        &token_price.mint,
+                    ),
+                });
+            }
+            (None, None) => (),
+        }
+
+        if let Some(merkle_root) = &phase.allow_list {
+            guards.allow_list = Some(AllowList {
+                merkle_root: merkle_root.clone(),
+            });
+        }
+
+        if let Some(limit) = phase.limit {
+            // each phase uses its own mint counter
+            guards.mint_limit = Some(MintLimit {
+                id: u8::try_from(index + 1)
+                    .map_err(|_| anyhow!("Too many phases with mint limit"))?,
+                limit,
+            });
+        }
+
+        groups.push(Group {
+            label: label.clone(),
+            guards,
+        });
+    }
+
+    Ok((groups, lints))
+}
+
+fn default_label(index: usize) -> String {
+    let label = format!("phase{}", index + 1);
+
+    if label.len() > MAX_LABEL_SIZE {
+        format!("ph{}", index + 1)
+    } else {
+        label
+    }
+}
+
+fn parse_timestamp(date: &str, label: &str) -> Result<i64> {
+    Ok(StartDate {
+        date: date.to_string(),
+    }
+    .to_guard_format()
+    .map_err(|err| anyhow!("Invalid date '{date}' for phase '{label}': {err}"))?
+    .date)
+}
+
+#[test]
+fn test_expand_phases() {
+    let phases_data: PhasesData = serde_json::from_str(
+        r#"{
+            "destination": "11111111111111111111111111111111",
+            "phases": [
+                {
+                    "label": "og",
+                    "start": "2022-10-20 18:00:00 +0000",
+                    "price": 0.5,
+                    "allowList": "a9a3dbd4a4e2e0a9e1d4d6e8a0b8c0fb1ad6b5c1c7e4b8e2f3a4b5c6d7e8f9a0",
+                    "limit": 1
+                },
+                {
+                    "start": "2022-10-20 20:00:00 +0000",
+                    "end": "2022-10-21 18:00:00 +0000",
+                    "price": 1
+                },
+                {
+                    "label": "public",
+                    "start": "2022-10-21 19:00:00 +0000",
+                    "price": 1.5,
+                    "limit": 3
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let (groups, lints) = expand_phases(&phases_data).unwrap();
+
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[1].label, "phase2");
+    // missing end dates default to the start of the next phase
+    assert_eq!(
+        groups[0].guards.end_date.as_ref().unwrap().date,
+        "2022-10-20 20:00:00 +0000"
+    );
+    assert!(groups[2].guards.end_date.is_none());
+    assert_eq!(groups[2].guards.mint_limit.as_ref().unwrap().id, 3);
+    // one hour gap between phase2 and public
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].location, "phase2");
+
+    let mut overlapping = phases_data;
+    overlapping.phases[1].end = Some("2022-10-21 20:00:00 +0000".to_string());
+    let (_, lints) = expand_phases(&overlapping).unwrap();
+    assert!(lints
+        .iter()
+        .any(|l| l.location == "phase2" && l.message.contains("overlaps")));
+
+    overlapping.phases[0].label = Some("toolong".to_string());
+    assert!(expand_phases(&overlapping).is_err());
+}
@@ -82,6 +82,7 @@ pub fn process_guard_remove(args: GuardRemoveArgs) -> Result<()> {
 
     pb.finish_and_clear();
     println!("{} {}", style("Signature:").bold(), sig);
+    push_output("signatures", sig.to_string());
 
     println!("\nThe candy guard is no longer the mint authority of the candy machine.");
     println!(
@@ -0,0 +1,232 @@
+use std::{ops::Deref, str::FromStr};
+
+use anchor_client::solana_sdk::{compute_budget::ComputeBudgetInstruction, pubkey::Pubkey};
+use anyhow::Result;
+use console::style;
+use mpl_candy_guard::{
+    accounts::Route as RouteAccount,
+    instruction::Route,
+    instructions::RouteArgs,
+    state::{CandyGuardData, GuardType, DATA_OFFSET},
+};
+use solana_program::instruction::AccountMeta;
+
+use crate::{cache::load_cache, common::*, pdas::*, utils::*};
+
+pub struct RouteAllocationArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub candy_machine: Option<String>,
+    pub candy_guard: Option<String>,
+    pub group: Option<String>,
+    pub priority_fee: u64,
+}
+
+pub fn process_route_allocation(args: RouteAllocationArgs) -> Result<()> {
+    println!(
+        "{} {}Loading candy guard",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    // the candy guard and candy machine ids specified take precedence over the
+    // ones from the cache
+
+    let candy_guard_id = match args.candy_guard {
+        Some(ref candy_guard_id) => candy_guard_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_guard
+        }
+    };
+
+    let candy_machine_id = match args.candy_machine {
+        Some(ref candy_machine_id) => candy_machine_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_machine
+        }
+    };
+
+    if candy_guard_id.is_empty() {
+        return Err(anyhow!("Missing candy guard id."));
+    }
+
+    if candy_machine_id.is_empty() {
+        return Err(anyhow!("Missing candy machine id."));
+    }
+
+    let candy_guard = Pubkey::from_str(&candy_guard_id)
+        .map_err(|_| anyhow!("Failed to parse candy guard id: {}", &candy_guard_id))?;
+
+    let candy_machine = Pubkey::from_str(&candy_machine_id)
+        .map_err(|_| anyhow!("Failed to parse candy machine id: {}", &candy_machine_id))?;
+
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(mpl_candy_guard::ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let account_data = program.rpc().get_account_data(&candy_guard)?;
+    let candy_guard_data = CandyGuardData::load(&account_data[DATA_OFFSET..])?;
+    let targets = get_allocation_targets(&candy_guard_data, &args.group)?;
+
+    pb.finish_with_message("Done");
+
+    println!("{} {}", style("Candy guard ID:").bold(), candy_guard);
+
+    println!(
+        "\n{} {}Initializing allocation trackers",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let rpc_client = program.rpc();
+
+    for (label, allocation_id) in targets {
+        let name = label.clone().unwrap_or_else(|| "default".to_string());
+
+        // initializing an existing tracker resets its count, so we only
+        // send the route instruction for trackers that do not exist yet
+        if let Some(count) =
+            get_allocation_count(&rpc_client, allocation_id, &candy_guard, &candy_machine)?
+        {
+            println!(
+                "{} allocation id {} already initialized ({} minted)",
+                style(format!("[{name}]")).bold(),
+                allocation_id,
+                count
+            );
+            continue;
+        }
+
+        let pb = spinner_with_style();
+        pb.set_message(format!("Initializing tracker for '{name}'..."));
+
+        let signature = initialize_allocation(
+            &program,
+            &candy_guard,
+            &candy_machine,
+            allocation_id,
+            &label,
+            args.priority_fee,
+        )?;
+
+        pb.finish_and_clear();
+        println!(
+            "{} allocation id {} {} {}",
+            style(format!("[{name}]")).bold(),
+            allocation_id,
+            style("Signature:").bold(),
+            signature
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the list of (label, allocation id) pairs that need a tracker. Groups
+/// inherit the allocation guard from the default guard set, in which case they
+/// share the same tracker.
+pub fn get_allocation_targets(
+    candy_guard_data: &CandyGuardData,
+    group: &Option<String>,
+) -> Result<Vec<(Option<String>, u8)>> {
+    let mut targets: Vec<(Option<String>, u8)> = Vec::new();
+    let default_allocation = candy_guard_data.default.allocation.as_ref();
+
+    match &candy_guard_data.groups {
+        Some(groups) if !groups.is_empty() => {
+            if let Some(label) = group {
+                if !groups.iter().any(|g| &g.label == label) {
+                    return Err(anyhow!("Could not find group with label '{label}'"));
+                }
+            }
+
+            for g in groups {
+                if group.as_ref().is_some_and(|label| &g.label != label) {
+                    continue;
+                }
+
+                if let Some(allocation) = g.guards.allocation.as_ref().or(default_allocation) {
+                    if !targets.iter().any(|(_, id)| *id == allocation.id) {
+                        targets.push((Some(g.label.clone()), allocation.id));
+                    }
+                }
+            }
+        }
+        _ => {
+            if let Some(label) = group {
+                return Err(anyhow!("Could not find group with label '{label}'"));
+            }
+
+            if let Some(allocation) = default_allocation {
+                targets.push((None, allocation.id));
+            }
+        }
+    }
+
+    if targets.is_empty() {
+        return Err(match group {
+            Some(label) => anyhow!("Missing allocation guard for group with label '{label}'"),
+            None => anyhow!("Missing allocation guard configuration"),
+        });
+    }
+
+    Ok(targets)
+}
+
+pub fn initialize_allocation<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+    allocation_id: u8,
+    label: &Option<String>,
+    priority_fee: u64,
+) -> Result<Signature> {
+    let (allocation_pda, _) =
+        find_allocation_tracker_pda(allocation_id, candy_guard_id, candy_machine_id);
+
+    let remaining_accounts = vec![
+        AccountMeta {
+            pubkey: allocation_pda,
+            is_signer: false,
+            is_writable: true,
+        },
+        AccountMeta {
+            pubkey: program.payer(),
+            is_signer: true,
+            is_writable: false,
+        },
+        AccountMeta {
+            pubkey: system_program::id(),
+            is_signer: false,
+            is_writable: false,
+        },
+    ];
+
+    let priority_fee_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
+
+    let builder = program
+        .request()
+        .instruction(priority_fee_ix)
+        .accounts(RouteAccount {
+            candy_guard: *candy_guard_id,
+            candy_machine: *candy_machine_id,
+            payer: program.payer(),
+        })
+        .accounts(remaining_accounts)
+        .args(Route {
+            args: RouteArgs {
+                data: vec![],
+                guard: GuardType::Allocation,
+            },
+            label: label.to_owned(),
+        });
+    let sig = builder.send()?;
+
+    Ok(sig)
+}
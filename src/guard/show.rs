@@ -1,24 +1,37 @@
-use std::str::FromStr;
+use std::{collections::hash_map::Entry, str::FromStr};
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anyhow::Result;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, TimeZone, Utc};
 use console::style;
 use mpl_candy_guard::state::{CandyGuard, CandyGuardData, GuardSet, DATA_OFFSET};
 use mpl_candy_machine_core::constants::EMPTY_STR;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 
-use crate::{cache::load_cache, common::*, show::print_with_style, utils::*};
+use crate::{
+    cache::load_cache,
+    common::*,
+    config::{base_units_to_ui_amount, parse_timezone, Timezone},
+    output::{merge_output, OutputFormat},
+    pdas::get_allocation_count,
+    show::print_with_style,
+    utils::*,
+};
 
 pub struct GuardShowArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
     pub candy_guard: Option<String>,
+    pub candy_machine: Option<String>,
+    pub timezone: Option<String>,
+    pub output: OutputFormat,
 }
 
 pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
-    println!("[1/1] {LOOKING_GLASS_EMOJI}Loading candy guard");
+    if !args.output.is_json() {
+        println!("[1/1] {LOOKING_GLASS_EMOJI}Loading candy guard");
+    }
 
     // the candy guard id specified takes precedence over the one from the cache
 
@@ -29,6 +42,25 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
         cache.program.candy_guard
     };
 
+    // the candy machine is only needed to report the guard usage, so we do not
+    // fail if it is not available
+    let candy_machine_id = if let Some(candy_machine) = args.candy_machine {
+        Some(candy_machine)
+    } else {
+        load_cache(&args.cache, false)
+            .ok()
+            .map(|cache| cache.program.candy_machine)
+            .filter(|candy_machine| !candy_machine.is_empty())
+    };
+
+    let candy_machine_id = match candy_machine_id {
+        Some(candy_machine_id) => Some(
+            Pubkey::from_str(&candy_machine_id)
+                .map_err(|_| anyhow!("Failed to parse candy machine id: {}", candy_machine_id))?,
+        ),
+        None => None,
+    };
+
     if candy_guard_id.is_empty() {
         return Err(anyhow!("Missing candy guard id."));
     }
@@ -42,6 +74,12 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
         }
     };
 
+    // dates are always shown in UTC, and optionally in the specified timezone
+    let timezone = match &args.timezone {
+        Some(timezone) => Some(parse_timezone(timezone)?),
+        None => None,
+    };
+
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
     let client = setup_client(&sugar_config)?;
     let program = client.program(mpl_candy_guard::ID);
@@ -54,6 +92,94 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
     // load the guard set information
     let candy_guard_data = CandyGuardData::load(&account_data[DATA_OFFSET..])?;
 
+    // current count of each allocation tracker
+    let allocations = if let Some(candy_machine_id) = candy_machine_id {
+        let rpc_client = program.rpc();
+        let mut allocations = HashMap::new();
+
+        let guard_sets = std::iter::once(&candy_guard_data.default).chain(
+            candy_guard_data
+                .groups
+                .iter()
+                .flatten()
+                .map(|group| &group.guards),
+        );
+
+        for guard_set in guard_sets {
+            if let Some(allocation) = &guard_set.allocation {
+                let count = get_allocation_count(
+                    &rpc_client,
+                    allocation.id,
+                    &candy_guard_id,
+                    &candy_machine_id,
+                )?;
+                allocations.insert(allocation.id, count);
+            }
+        }
+
+        Some(allocations)
+    } else {
+        None
+    };
+
+    // decimals of the token mints, used to show the amounts in UI units
+    let mut decimals = HashMap::new();
+
+    let guard_sets = std::iter::once(&candy_guard_data.default).chain(
+        candy_guard_data
+            .groups
+            .iter()
+            .flatten()
+            .map(|group| &group.guards),
+    );
+
+    for guard_set in guard_sets {
+        let mints = [
+            guard_set.token_payment.as_ref().map(|guard| guard.mint),
+            guard_set.token_gate.as_ref().map(|guard| guard.mint),
+            guard_set.token_burn.as_ref().map(|guard| guard.mint),
+            guard_set
+                .freeze_token_payment
+                .as_ref()
+                .map(|guard| guard.mint),
+            guard_set.token2022_payment.as_ref().map(|guard| guard.mint),
+        ];
+
+        for mint in mints.into_iter().flatten() {
+            if let Entry::Vacant(entry) = decimals.entry(mint) {
+                // amounts are shown in base units only if the mint cannot be loaded
+                if let Ok(mint_decimals) = get_mint_decimals(&program, &mint) {
+                    entry.insert(mint_decimals);
+                }
+            }
+        }
+    }
+
+    if args.output.is_json() {
+        pb.finish_and_clear();
+
+        let groups = candy_guard_data.groups.as_ref().map(|groups| {
+            groups
+                .iter()
+                .map(|group| {
+                    json!({
+                        "label": group.label,
+                        "guards": guard_set_output(&group.guards, &allocations, &decimals),
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+
+        return merge_output(&json!({
+            "candyGuard": candy_guard_id.to_string(),
+            "base": account.base.to_string(),
+            "bump": account.bump,
+            "authority": account.authority.to_string(),
+            "default": guard_set_output(&candy_guard_data.default, &allocations, &decimals),
+            "groups": groups,
+        }));
+    }
+
     pb.finish_with_message("Done");
 
     println!(
@@ -73,7 +199,13 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
 
     // default guard set
     print_with_style("    ", "default", EMPTY_STR.to_string());
-    print_guard_set(&candy_guard_data.default, "    :   ".to_string())?;
+    print_guard_set(
+        &candy_guard_data.default,
+        "    :   ".to_string(),
+        &allocations,
+        &decimals,
+        &timezone,
+    )?;
 
     // groups
     if let Some(groups) = candy_guard_data.groups {
@@ -93,6 +225,9 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
                 } else {
                     "         :   ".to_string()
                 },
+                &allocations,
+                &decimals,
+                &timezone,
             )?;
         }
     } else {
@@ -102,7 +237,13 @@ pub fn process_guard_show(args: GuardShowArgs) -> Result<()> {
     Ok(())
 }
 
-fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
+fn print_guard_set(
+    guard_set: &GuardSet,
+    padding: String,
+    allocations: &Option<HashMap<u8, Option<u32>>>,
+    decimals: &HashMap<Pubkey, u8>,
+    timezone: &Option<Timezone>,
+) -> Result<()> {
     // bot tax
     if let Some(bot_tax) = &guard_set.bot_tax {
         print_with_style(&padding, "bot tax", EMPTY_STR.to_string());
@@ -151,7 +292,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
         print_with_style(
             &format!("{padding}:   "),
             "amount",
-            token_payment.amount.to_string(),
+            format_token_amount(token_payment.amount, &token_payment.mint, decimals),
         );
         print_with_style(
             &format!("{padding}:   "),
@@ -174,7 +315,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
             print_with_style(
                 &format!("{padding}:   "),
                 "date",
-                date.format("%a %B %e %Y %H:%M:%S UTC").to_string(),
+                format_date(&date, timezone),
             );
         } else {
             // this should not happen, but adding a message so it can be
@@ -203,7 +344,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
         print_with_style(
             &format!("{padding}:   "),
             "amount",
-            token_gate.amount.to_string(),
+            format_token_amount(token_gate.amount, &token_gate.mint, decimals),
         );
         print_with_style(
             &format!("{padding}:   "),
@@ -238,7 +379,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
             print_with_style(
                 &format!("{padding}:   "),
                 "date",
-                date.format("%a %B %e %Y %H:%M:%S UTC").to_string(),
+                format_date(&date, timezone),
             );
         } else {
             // this should not happen, but adding a message so it can be
@@ -345,7 +486,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
         print_with_style(
             &format!("{padding}    "),
             "amount",
-            token_burn.amount.to_string(),
+            format_token_amount(token_burn.amount, &token_burn.mint, decimals),
         );
         print_with_style(
             &format!("{padding}    "),
@@ -383,7 +524,11 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
         print_with_style(
             &format!("{padding}:   "),
             "amount",
-            freeze_token_payment.amount.to_string(),
+            format_token_amount(
+                freeze_token_payment.amount,
+                &freeze_token_payment.mint,
+                decimals,
+            ),
         );
         print_with_style(
             &format!("{padding}:   "),
@@ -426,6 +571,16 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
             "limit",
             allocation.limit.to_string(),
         );
+        if let Some(allocations) = allocations {
+            print_with_style(
+                &format!("{padding}:   "),
+                "usage",
+                match allocations.get(&allocation.id) {
+                    Some(Some(count)) => format!("{} of {}", count, allocation.limit),
+                    _ => "tracker not initialized".to_string(),
+                },
+            );
+        }
     } else {
         print_with_style(&padding, "allocation", "none".to_string());
     }
@@ -436,7 +591,7 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
         print_with_style(
             &format!("{padding}    "),
             "amount",
-            token2022_payment.amount.to_string(),
+            format_token_amount(token2022_payment.amount, &token2022_payment.mint, decimals),
         );
         print_with_style(
             &format!("{padding}    "),
@@ -454,3 +609,136 @@ fn print_guard_set(guard_set: &GuardSet, padding: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Returns the JSON representation of a guard set, with `null` for the guards
+/// that are not enabled.
+fn guard_set_output(
+    guard_set: &GuardSet,
+    allocations: &Option<HashMap<u8, Option<u32>>>,
+    decimals: &HashMap<Pubkey, u8>,
+) -> Value {
+    let token_amount = |amount: u64, mint: &Pubkey| {
+        json!({
+            "amount": amount,
+            "uiAmount": decimals
+                .get(mint)
+                .map(|decimals| base_units_to_ui_amount(amount, *decimals)),
+        })
+    };
+    let date = |timestamp: i64| {
+        json!({
+            "timestamp": timestamp,
+            "utc": NaiveDateTime::from_timestamp_opt(timestamp, 0)
+                .map(|date| Utc.from_utc_datetime(&date).to_rfc3339()),
+        })
+    };
+
+    json!({
+        "botTax": guard_set.bot_tax.as_ref().map(|guard| json!({
+            "lamports": guard.lamports,
+            "lastInstruction": guard.last_instruction,
+        })),
+        "solPayment": guard_set.sol_payment.as_ref().map(|guard| json!({
+            "lamports": guard.lamports,
+            "destination": guard.destination.to_string(),
+        })),
+        "tokenPayment": guard_set.token_payment.as_ref().map(|guard| json!({
+            "amount": token_amount(guard.amount, &guard.mint),
+            "mint": guard.mint.to_string(),
+            "destinationAta": guard.destination_ata.to_string(),
+        })),
+        "startDate": guard_set.start_date.as_ref().map(|guard| date(guard.date)),
+        "thirdPartySigner": guard_set.third_party_signer.as_ref().map(|guard| json!({
+            "signerKey": guard.signer_key.to_string(),
+        })),
+        "tokenGate": guard_set.token_gate.as_ref().map(|guard| json!({
+            "amount": token_amount(guard.amount, &guard.mint),
+            "mint": guard.mint.to_string(),
+        })),
+        "gatekeeper": guard_set.gatekeeper.as_ref().map(|guard| json!({
+            "gatekeeperNetwork": guard.gatekeeper_network.to_string(),
+            "expireOnUse": guard.expire_on_use,
+        })),
+        "endDate": guard_set.end_date.as_ref().map(|guard| date(guard.date)),
+        "allowList": guard_set.allow_list.as_ref().map(|guard| json!({
+            "merkleRoot": hex::encode(guard.merkle_root),
+        })),
+        "mintLimit": guard_set.mint_limit.as_ref().map(|guard| json!({
+            "id": guard.id,
+            "limit": guard.limit,
+        })),
+        "nftPayment": guard_set.nft_payment.as_ref().map(|guard| json!({
+            "requiredCollection": guard.required_collection.to_string(),
+            "destination": guard.destination.to_string(),
+        })),
+        "redeemedAmount": guard_set.redeemed_amount.as_ref().map(|guard| json!({
+            "maximum": guard.maximum,
+        })),
+        "addressGate": guard_set.address_gate.as_ref().map(|guard| json!({
+            "address": guard.address.to_string(),
+        })),
+        "nftGate": guard_set.nft_gate.as_ref().map(|guard| json!({
+            "requiredCollection": guard.required_collection.to_string(),
+        })),
+        "nftBurn": guard_set.nft_burn.as_ref().map(|guard| json!({
+            "requiredCollection": guard.required_collection.to_string(),
+        })),
+        "tokenBurn": guard_set.token_burn.as_ref().map(|guard| json!({
+            "amount": token_amount(guard.amount, &guard.mint),
+            "mint": guard.mint.to_string(),
+        })),
+        "freezeSolPayment": guard_set.freeze_sol_payment.as_ref().map(|guard| json!({
+            "lamports": guard.lamports,
+            "destination": guard.destination.to_string(),
+        })),
+        "freezeTokenPayment": guard_set.freeze_token_payment.as_ref().map(|guard| json!({
+            "amount": token_amount(guard.amount, &guard.mint),
+            "mint": guard.mint.to_string(),
+            "destinationAta": guard.destination_ata.to_string(),
+        })),
+        "programGate": guard_set.program_gate.as_ref().map(|guard| json!({
+            "additional": guard
+                .additional
+                .iter()
+                .map(|program| program.to_string())
+                .collect::<Vec<_>>(),
+        })),
+        "allocation": guard_set.allocation.as_ref().map(|guard| json!({
+            "id": guard.id,
+            "limit": guard.limit,
+            "usage": allocations
+                .as_ref()
+                .and_then(|allocations| allocations.get(&guard.id).copied().flatten()),
+        })),
+        "token2022Payment": guard_set.token2022_payment.as_ref().map(|guard| json!({
+            "amount": token_amount(guard.amount, &guard.mint),
+            "mint": guard.mint.to_string(),
+            "destinationAta": guard.destination_ata.to_string(),
+        })),
+    })
+}
+
+fn format_token_amount(amount: u64, mint: &Pubkey, decimals: &HashMap<Pubkey, u8>) -> String {
+    match decimals.get(mint) {
+        Some(decimals) => format!(
+            "{} ({})",
+            amount,
+            base_units_to_ui_amount(amount, *decimals)
+        ),
+        None => amount.to_string(),
+    }
+}
+
+fn format_date(date: &NaiveDateTime, timezone: &Option<Timezone>) -> String {
+    let utc = date.format("%a %B %e %Y %H:%M:%S UTC").to_string();
+
+    match timezone {
+        Some(timezone) => format!(
+            "{utc} ({})",
+            Utc.from_utc_datetime(date)
+                .with_timezone(&timezone.offset_at(date))
+                .format("%a %B %e %Y %H:%M:%S %:z")
+        ),
+        None => utc,
+    }
+}
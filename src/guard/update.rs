@@ -5,7 +5,14 @@ use anyhow::Result;
 use console::style;
 use mpl_candy_guard::{accounts::Update as UpdateAccount, instruction::Update};
 
-use crate::{cache::load_cache, common::*, config::get_config_data, utils::*};
+use crate::{
+    cache::load_cache,
+    common::*,
+    config::get_config_data,
+    tx::{get_tx_authority, send_or_export, TxExport},
+    utils::*,
+    validate::validate_guards,
+};
 
 pub struct GuardUpdateArgs {
     pub keypair: Option<String>,
@@ -14,6 +21,8 @@ pub struct GuardUpdateArgs {
     pub config: String,
     pub candy_guard: Option<String>,
     pub priority_fee: u64,
+    pub export_tx: Option<TxExport>,
+    pub nonce: Option<Pubkey>,
 }
 
 pub fn process_guard_update(args: GuardUpdateArgs) -> Result<()> {
@@ -48,7 +57,7 @@ pub fn process_guard_update(args: GuardUpdateArgs) -> Result<()> {
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
     let client = setup_client(&sugar_config)?;
     let program = client.program(mpl_candy_guard::ID);
-    let payer = sugar_config.keypair;
+    let authority = get_tx_authority(&args.export_tx, sugar_config.keypair.pubkey());
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
@@ -64,8 +73,11 @@ pub fn process_guard_update(args: GuardUpdateArgs) -> Result<()> {
         COMPUTER_EMOJI
     );
 
-    let config_data = get_config_data(&args.config)?;
-    let data = if let Some(guards) = &config_data.guards {
+    let mut config_data = get_config_data(&args.config)?;
+    let data = if let Some(guards) = &mut config_data.guards {
+        // checks the guards configuration before sending the update
+        validate_guards(&program, guards)?;
+        resolve_token_amounts(&program, guards)?;
         guards.to_guard_format()?
     } else {
         return Err(anyhow!("Missing guards configuration."));
@@ -84,18 +96,30 @@ pub fn process_guard_update(args: GuardUpdateArgs) -> Result<()> {
         .instruction(priority_fee)
         .accounts(UpdateAccount {
             candy_guard: candy_guard_id,
-            authority: payer.pubkey(),
-            payer: payer.pubkey(),
+            authority,
+            payer: authority,
             system_program: system_program::ID,
         })
         .args(Update {
             data: serialized_data,
         });
 
-    let sig = tx.send()?;
-
-    pb.finish_and_clear();
-    println!("{} {}", style("Signature:").bold(), sig);
+    let signature = send_or_export(
+        &program.rpc(),
+        &tx.instructions()?,
+        &sugar_config.keypair,
+        &authority,
+        &args.export_tx,
+        &args.nonce,
+        "Update candy guard",
+    )?;
+
+    if let Some(sig) = signature {
+        pb.finish_and_clear();
+        println!("{} {}", style("Signature:").bold(), sig);
+    } else {
+        pb.finish_with_message("Update transaction exported");
+    }
 
     Ok(())
 }
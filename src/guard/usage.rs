@@ -0,0 +1,274 @@
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use console::style;
+use mpl_candy_guard::{
+    guards::GuardSet,
+    state::{CandyGuardData, DATA_OFFSET},
+};
+use solana_client::rpc_client::RpcClient;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::{
+    cache::load_cache, candy_machine::CANDY_MACHINE_ID, common::*, freeze::find_freeze_pda,
+    pdas::*, utils::*,
+};
+
+pub struct GuardUsageArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub candy_machine: Option<String>,
+    pub candy_guard: Option<String>,
+    pub wallet: Option<String>,
+}
+
+/// Live usage of a guard.
+pub struct GuardUsage {
+    /// Guard set ("default" or the group label).
+    pub location: String,
+    pub guard: &'static str,
+    pub id: Option<u8>,
+    pub usage: String,
+}
+
+pub struct UsageContext {
+    pub rpc_client: RpcClient,
+    pub candy_guard: Pubkey,
+    pub candy_machine: Pubkey,
+    pub items_redeemed: u64,
+    pub wallet: Option<Pubkey>,
+}
+
+pub fn process_guard_usage(args: GuardUsageArgs) -> Result<()> {
+    println!(
+        "{} {}Loading candy guard",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    // the candy guard and candy machine ids specified take precedence over the
+    // ones from the cache
+
+    let candy_guard_id = match args.candy_guard {
+        Some(ref candy_guard_id) => candy_guard_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_guard
+        }
+    };
+
+    let candy_machine_id = match args.candy_machine {
+        Some(ref candy_machine_id) => candy_machine_id.to_owned(),
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_machine
+        }
+    };
+
+    if candy_guard_id.is_empty() {
+        return Err(anyhow!("Missing candy guard id."));
+    }
+
+    if candy_machine_id.is_empty() {
+        return Err(anyhow!("Missing candy machine id."));
+    }
+
+    let candy_guard = Pubkey::from_str(&candy_guard_id)
+        .map_err(|_| anyhow!("Failed to parse candy guard id: {}", &candy_guard_id))?;
+
+    let candy_machine = Pubkey::from_str(&candy_machine_id)
+        .map_err(|_| anyhow!("Failed to parse candy machine id: {}", &candy_machine_id))?;
+
+    let wallet = match &args.wallet {
+        Some(wallet) => Some(
+            Pubkey::from_str(wallet)
+                .map_err(|_| anyhow!("Failed to parse wallet address: {}", wallet))?,
+        ),
+        None => None,
+    };
+
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let account_data = program.rpc().get_account_data(&candy_guard)?;
+    let candy_guard_data = CandyGuardData::load(&account_data[DATA_OFFSET..])?;
+    let candy_machine_state: CandyMachine = program.account(candy_machine)?;
+
+    pb.finish_with_message("Done");
+
+    println!("{} {}", style("Candy guard ID:").bold(), candy_guard);
+    println!(
+        "{} {} of {}",
+        style("Items redeemed:").bold(),
+        candy_machine_state.items_redeemed,
+        candy_machine_state.data.items_available
+    );
+    if let Some(wallet) = &wallet {
+        println!("{} {}", style("Wallet:").bold(), wallet);
+    }
+
+    println!(
+        "\n{} {}Reading guard usage",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Reading usage accounts...");
+
+    let context = UsageContext {
+        rpc_client: program.rpc(),
+        candy_guard,
+        candy_machine,
+        items_redeemed: candy_machine_state.items_redeemed,
+        wallet,
+    };
+
+    // groups are evaluated with the guards inherited from the default set
+    let mut guard_sets = Vec::new();
+
+    match &candy_guard_data.groups {
+        Some(groups) if !groups.is_empty() => {
+            for group in groups {
+                let guard_set = CandyGuardData::active_set(
+                    &account_data[DATA_OFFSET..],
+                    Some(group.label.clone()),
+                )?;
+                guard_sets.push((group.label.clone(), *guard_set));
+            }
+        }
+        _ => guard_sets.push(("default".to_string(), candy_guard_data.default.clone())),
+    }
+
+    let mut usages = Vec::new();
+
+    for (location, guard_set) in &guard_sets {
+        usages.extend(get_guard_set_usage(&context, location, guard_set)?);
+    }
+
+    pb.finish_and_clear();
+
+    if usages.is_empty() {
+        println!(
+            "\n{}{}",
+            PAPER_EMOJI,
+            style("No guards with usage tracking found.").dim()
+        );
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+    builder.set_header(["group", "guard", "id", "usage"]);
+
+    for usage in &usages {
+        builder.push_record([
+            usage.location.clone(),
+            usage.guard.to_string(),
+            usage.id.map(|id| id.to_string()).unwrap_or_default(),
+            usage.usage.clone(),
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::rounded());
+    println!("\n{table}");
+
+    Ok(())
+}
+
+/// Returns the usage of the guards that keep track of mints (mint limit,
+/// redeemed amount, allocation and freeze escrows).
+pub fn get_guard_set_usage(
+    context: &UsageContext,
+    location: &str,
+    guard_set: &GuardSet,
+) -> Result<Vec<GuardUsage>> {
+    let rpc_client = &context.rpc_client;
+    let mut usages = Vec::new();
+
+    if let Some(guard) = &guard_set.redeemed_amount {
+        usages.push(GuardUsage {
+            location: location.to_string(),
+            guard: "redeemedAmount",
+            id: None,
+            usage: format!("{} of {}", context.items_redeemed, guard.maximum),
+        });
+    }
+
+    if let Some(guard) = &guard_set.allocation {
+        let count = get_allocation_count(
+            rpc_client,
+            guard.id,
+            &context.candy_guard,
+            &context.candy_machine,
+        )?;
+
+        usages.push(GuardUsage {
+            location: location.to_string(),
+            guard: "allocation",
+            id: Some(guard.id),
+            usage: match count {
+                Some(count) => format!("{count} of {}", guard.limit),
+                None => "tracker not initialized".to_string(),
+            },
+        });
+    }
+
+    if let Some(guard) = &guard_set.mint_limit {
+        // mint counters are per wallet, so we can only report the limit when
+        // no wallet is specified
+        let usage = match &context.wallet {
+            Some(wallet) => {
+                let count = get_mint_counter(
+                    rpc_client,
+                    guard.id,
+                    wallet,
+                    &context.candy_guard,
+                    &context.candy_machine,
+                )?
+                .unwrap_or(0);
+                format!("{count} of {} (wallet)", guard.limit)
+            }
+            None => format!("limit of {} per wallet", guard.limit),
+        };
+
+        usages.push(GuardUsage {
+            location: location.to_string(),
+            guard: "mintLimit",
+            id: Some(guard.id),
+            usage,
+        });
+    }
+
+    let mut escrows = Vec::new();
+
+    if let Some(guard) = &guard_set.freeze_sol_payment {
+        escrows.push(("freezeSolPayment", guard.destination));
+    }
+    if let Some(guard) = &guard_set.freeze_token_payment {
+        escrows.push(("freezeTokenPayment", guard.destination_ata));
+    }
+
+    for (guard, destination) in escrows {
+        let (freeze_escrow, _) =
+            find_freeze_pda(&context.candy_guard, &context.candy_machine, &destination);
+
+        usages.push(GuardUsage {
+            location: location.to_string(),
+            guard,
+            id: None,
+            usage: match get_frozen_count(rpc_client, &freeze_escrow)? {
+                Some(count) => format!("{count} frozen"),
+                None => "escrow not initialized".to_string(),
+            },
+        });
+    }
+
+    Ok(usages)
+}
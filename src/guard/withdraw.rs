@@ -73,6 +73,7 @@ pub fn process_guard_withdraw(args: GuardWithdrawArgs) -> Result<()> {
 
     pb.finish_and_clear();
     println!("{} {}", style("Signature:").bold(), sig);
+    push_output("signatures", sig.to_string());
 
     println!(
         "\nReceived ◎ {} from rent fee.",
@@ -5,7 +5,7 @@ use console::{style, Style};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 
 use crate::{
-    common::LAUNCH_EMOJI,
+    common::{DEFAULT_VERIFY_REPORT, LAUNCH_EMOJI},
     config::parser::get_config_data,
     create_config::{process_create_config, CreateConfigArgs},
     deploy::{process_deploy, DeployArgs},
@@ -60,6 +60,7 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
 
     let validate_args = ValidateArgs {
         assets_dir: args.assets_dir.clone(),
+        config: args.config.clone(),
         strict: args.strict,
         skip_collection_prompt: args.skip_collection_prompt,
     };
@@ -90,6 +91,8 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
         interrupted: args.interrupted.clone(),
         collection_mint: None,
         priority_fee: args.priority_fee,
+        pack_config_lines: false,
+        dry_run: false,
     };
 
     process_deploy(deploy_args).await?;
@@ -100,9 +103,11 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
         keypair: args.keypair.clone(),
         rpc_url: args.rpc_url.clone(),
         cache: args.cache.clone(),
+        deep: false,
+        report: DEFAULT_VERIFY_REPORT.to_string(),
     };
 
-    process_verify(verify_args)?;
+    process_verify(verify_args).await?;
 
     Ok(())
 }
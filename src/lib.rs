@@ -15,6 +15,8 @@ pub mod guard;
 pub mod hash;
 pub mod launch;
 pub mod mint;
+pub mod mints;
+pub mod output;
 pub mod parse;
 pub mod pdas;
 pub mod program_errors;
@@ -22,6 +24,9 @@ pub mod reveal;
 pub mod setup;
 pub mod show;
 pub mod sign;
+pub mod signer;
+pub mod snapshot;
+pub mod tx;
 pub mod update;
 pub mod upload;
 pub mod utils;
@@ -8,13 +8,17 @@ use std::{
     },
 };
 
+use anchor_client::solana_sdk::pubkey::Pubkey;
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use console::style;
 use sugar_cli::{
-    airdrop::{process_airdrop, AirdropArgs},
+    airdrop::{process_airdrop, process_airdrop_reconcile, AirdropArgs, AirdropReconcileArgs},
     bundlr::{process_bundlr, BundlrArgs},
-    cli::{Cli, CollectionSubcommands, Commands, ConfigSubcommands, FreezeCommand, GuardCommand},
+    cli::{
+        AirdropCommand, Cli, CollectionSubcommands, Commands, ConfigSubcommands, FreezeCommand,
+        GuardCommand, GuardGroupCommand, GuardRouteCommand, TxCommand,
+    },
     collections::{process_set_collection, SetCollectionArgs},
     constants::{COMPLETE_EMOJI, ERROR_EMOJI},
     create_config::{process_create_config, CreateConfigArgs},
@@ -24,19 +28,26 @@ use sugar_cli::{
         UnlockFundsArgs,
     },
     guard::{
-        process_guard_add, process_guard_remove, process_guard_show, process_guard_update,
-        process_guard_withdraw, GuardAddArgs, GuardRemoveArgs, GuardShowArgs, GuardUpdateArgs,
-        GuardWithdrawArgs,
+        process_guard_add, process_guard_check, process_guard_group, process_guard_phases,
+        process_guard_remove, process_guard_show, process_guard_update, process_guard_usage,
+        process_guard_withdraw, process_route_allocation, GroupAction, GuardAddArgs,
+        GuardCheckArgs, GuardGroupArgs, GuardPhasesArgs, GuardRemoveArgs, GuardShowArgs,
+        GuardUpdateArgs, GuardUsageArgs, GuardWithdrawArgs, RouteAllocationArgs,
     },
     hash::{process_hash, HashArgs},
     launch::{process_launch, LaunchArgs},
     mint::{process_mint, MintArgs},
+    mints::{process_mints, MintsArgs},
+    output::{is_json_output, print_error_output, print_output, set_output_format},
     parse::parse_sugar_errors,
     reveal::{process_reveal, RevealArgs},
     show::{process_show, ShowArgs},
     sign::{process_sign, SignArgs},
+    snapshot::{process_snapshot, SnapshotArgs},
+    tx::{process_tx_create_nonce, process_tx_submit, TxCreateNonceArgs, TxExport, TxSubmitArgs},
     update::{process_set_token_stardard, process_update, SetTokenStandardArgs, UpdateArgs},
     upload::{process_upload, UploadArgs},
+    utils::run_interrupt_hook,
     validate::{process_validate, ValidateArgs},
     verify::{process_verify, VerifyArgs},
     withdraw::{process_withdraw, WithdrawArgs},
@@ -85,6 +96,13 @@ async fn main() {
                 COMPLETE_EMOJI,
                 style("Command successful.").green().bold().dim()
             );
+
+            if is_json_output() {
+                if let Err(err) = print_output() {
+                    eprintln!("Failed to print the command output: {err}");
+                    std::process::exit(1);
+                }
+            }
         }
         Err(err) => {
             let parsed_err = parse_sugar_errors(&err.to_string());
@@ -95,6 +113,12 @@ async fn main() {
                 style("Error running command (re-run needed):").red(),
                 parsed_err,
             );
+
+            if is_json_output() {
+                print_error_output(&err).ok();
+            }
+            // restores any temporary on-chain change before exiting
+            run_interrupt_hook();
             // finished the program with an error code to the OS
             std::process::exit(1);
         }
@@ -106,6 +130,9 @@ async fn run() -> Result<()> {
 
     let cli = Cli::parse();
 
+    // in JSON mode, the progress output goes to stderr
+    set_output_format(cli.output)?;
+
     let log_level_error: Result<()> = Err(anyhow!(
         "Invalid log level: {:?}.\n Valid levels are: trace, debug, info, warn, error.",
         cli.log_level
@@ -134,6 +161,8 @@ async fn run() -> Result<()> {
                 ERROR_EMOJI,
                 style("Error running command (re-run needed):").red(),
             );
+            // restores any temporary on-chain change before exiting
+            run_interrupt_hook();
             // finished the program with an error code to the OS
             std::process::exit(1);
         }
@@ -142,6 +171,42 @@ async fn run() -> Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    let export_tx = match cli.export_tx {
+        Some(path) => {
+            if !supports_tx_export(&cli.command) {
+                return Err(anyhow!(
+                    "--export-tx is only supported by 'config update', 'guard update', \
+                    'collection set', 'withdraw' and 'freeze unlock-funds'"
+                ));
+            }
+            Some(TxExport::new(
+                path,
+                cli.export_authority,
+                cli.export_encoding,
+            )?)
+        }
+        None => None,
+    };
+
+    let nonce = match cli.nonce {
+        Some(nonce) => {
+            if !supports_tx_export(&cli.command) && !matches!(cli.command, Commands::Reveal { .. })
+            {
+                return Err(anyhow!(
+                    "--nonce is only supported by 'config update', 'guard update', \
+                    'collection set', 'withdraw', 'freeze unlock-funds' and 'reveal'"
+                ));
+            }
+            Some(
+                Pubkey::from_str(&nonce)
+                    .map_err(|_| anyhow!("Failed to parse nonce account: {}", nonce))?,
+            )
+        }
+        None => None,
+    };
+
+    let discovery = cli.mint_discovery;
+
     match cli.command {
         Commands::Bundlr {
             keypair,
@@ -172,6 +237,8 @@ async fn run() -> Result<()> {
                 config,
                 candy_machine,
                 priority_fee,
+                export_tx: export_tx.clone(),
+                nonce,
             })?,
         },
         Commands::Config { command } => match command {
@@ -202,6 +269,8 @@ async fn run() -> Result<()> {
                 new_authority,
                 candy_machine,
                 priority_fee,
+                export_tx: export_tx.clone(),
+                nonce,
             })?,
             ConfigSubcommands::Set {
                 keypair,
@@ -228,6 +297,8 @@ async fn run() -> Result<()> {
             cache,
             collection_mint,
             priority_fee,
+            pack_config_lines,
+            dry_run,
         } => {
             process_deploy(DeployArgs {
                 config,
@@ -237,6 +308,8 @@ async fn run() -> Result<()> {
                 interrupted: interrupted.clone(),
                 collection_mint,
                 priority_fee,
+                pack_config_lines,
+                dry_run,
             })
             .await?
         }
@@ -293,6 +366,7 @@ async fn run() -> Result<()> {
                     timeout,
                     token,
                     priority_fee,
+                    discovery,
                 })
                 .await?
             }
@@ -318,6 +392,8 @@ async fn run() -> Result<()> {
                 label,
                 token,
                 priority_fee,
+                export_tx: export_tx.clone(),
+                nonce,
             })?,
         },
         Commands::Guard { command } => match command {
@@ -338,6 +414,132 @@ async fn run() -> Result<()> {
                 candy_guard,
                 priority_fee,
             })?,
+            GuardCommand::Check {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                candy_guard,
+                wallet,
+                group,
+                at,
+            } => process_guard_check(GuardCheckArgs {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                candy_guard,
+                wallet,
+                group,
+                at,
+            })?,
+            GuardCommand::Group { command } => match command {
+                GuardGroupCommand::Add {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    priority_fee,
+                    config,
+                    candy_guard,
+                    update,
+                    label,
+                    from,
+                } => process_guard_group(GuardGroupArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    config,
+                    candy_guard,
+                    priority_fee,
+                    update,
+                    action: GroupAction::Add { label, from },
+                })?,
+                GuardGroupCommand::Remove {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    priority_fee,
+                    config,
+                    candy_guard,
+                    update,
+                    label,
+                } => process_guard_group(GuardGroupArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    config,
+                    candy_guard,
+                    priority_fee,
+                    update,
+                    action: GroupAction::Remove { label },
+                })?,
+                GuardGroupCommand::Rename {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    priority_fee,
+                    config,
+                    candy_guard,
+                    update,
+                    label,
+                    new_label,
+                } => process_guard_group(GuardGroupArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    config,
+                    candy_guard,
+                    priority_fee,
+                    update,
+                    action: GroupAction::Rename { label, new_label },
+                })?,
+                GuardGroupCommand::Set {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    priority_fee,
+                    config,
+                    candy_guard,
+                    update,
+                    label,
+                    field,
+                    value,
+                } => process_guard_group(GuardGroupArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    config,
+                    candy_guard,
+                    priority_fee,
+                    update,
+                    action: GroupAction::Set {
+                        label,
+                        field,
+                        value,
+                    },
+                })?,
+            },
+            GuardCommand::Phases {
+                keypair,
+                rpc_url,
+                cache,
+                priority_fee,
+                config,
+                candy_guard,
+                update,
+                force,
+                phases,
+            } => process_guard_phases(GuardPhasesArgs {
+                keypair,
+                rpc_url,
+                cache,
+                config,
+                candy_guard,
+                priority_fee,
+                phases,
+                update,
+                force,
+            })?,
             GuardCommand::Remove {
                 keypair,
                 rpc_url,
@@ -353,16 +555,40 @@ async fn run() -> Result<()> {
                 candy_guard,
                 priority_fee,
             })?,
+            GuardCommand::Route { command } => match command {
+                GuardRouteCommand::Allocation {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    priority_fee,
+                    candy_guard,
+                    candy_machine,
+                    group,
+                } => process_route_allocation(RouteAllocationArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    candy_machine,
+                    candy_guard,
+                    group,
+                    priority_fee,
+                })?,
+            },
             GuardCommand::Show {
                 keypair,
                 rpc_url,
                 cache,
                 candy_guard,
+                candy_machine,
+                timezone,
             } => process_guard_show(GuardShowArgs {
                 keypair,
                 rpc_url,
                 cache,
                 candy_guard,
+                candy_machine,
+                timezone,
+                output: cli.output,
             })?,
             GuardCommand::Update {
                 keypair,
@@ -378,6 +604,23 @@ async fn run() -> Result<()> {
                 config,
                 candy_guard,
                 priority_fee,
+                export_tx: export_tx.clone(),
+                nonce,
+            })?,
+            GuardCommand::Usage {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                candy_guard,
+                wallet,
+            } => process_guard_usage(GuardUsageArgs {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                candy_guard,
+                wallet,
             })?,
             GuardCommand::Withdraw {
                 keypair,
@@ -433,6 +676,9 @@ async fn run() -> Result<()> {
             receiver,
             candy_machine,
             priority_fee,
+            concurrency,
+            retries,
+            results,
         } => {
             process_mint(MintArgs {
                 keypair,
@@ -442,6 +688,9 @@ async fn run() -> Result<()> {
                 receiver,
                 candy_machine,
                 priority_fee,
+                concurrency,
+                retries,
+                results,
             })
             .await?
         }
@@ -451,18 +700,34 @@ async fn run() -> Result<()> {
             cache,
             candy_machine,
             airdrop_list,
+            results,
             priority_fee,
-        } => {
-            process_airdrop(AirdropArgs {
+            unwrap,
+            command,
+        } => match command {
+            Some(AirdropCommand::Reconcile {
                 keypair,
                 rpc_url,
-                cache,
-                candy_machine,
-                airdrop_list,
-                priority_fee,
-            })
-            .await?
-        }
+                results,
+            }) => process_airdrop_reconcile(AirdropReconcileArgs {
+                keypair,
+                rpc_url,
+                results,
+            })?,
+            None => {
+                process_airdrop(AirdropArgs {
+                    keypair,
+                    rpc_url,
+                    cache,
+                    candy_machine,
+                    airdrop_list,
+                    results,
+                    priority_fee,
+                    unwrap,
+                })
+                .await?
+            }
+        },
         Commands::Reveal {
             keypair,
             rpc_url,
@@ -478,6 +743,46 @@ async fn run() -> Result<()> {
                 config,
                 timeout,
                 new_update_authority,
+                nonce,
+                discovery,
+            })
+            .await?
+        }
+        Commands::Mints {
+            keypair,
+            rpc_url,
+            cache,
+            candy_machine,
+            file,
+            timeout,
+        } => {
+            process_mints(MintsArgs {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                file,
+                timeout,
+                discovery,
+            })
+            .await?
+        }
+        Commands::Snapshot {
+            keypair,
+            rpc_url,
+            cache,
+            candy_machine,
+            file,
+            timeout,
+        } => {
+            process_snapshot(SnapshotArgs {
+                keypair,
+                rpc_url,
+                cache,
+                candy_machine,
+                file,
+                timeout,
+                discovery,
             })
             .await?
         }
@@ -493,7 +798,28 @@ async fn run() -> Result<()> {
             cache,
             candy_machine,
             unminted,
+            output: cli.output,
         })?,
+        Commands::Tx { command } => match command {
+            TxCommand::CreateNonce {
+                keypair,
+                rpc_url,
+                authority,
+            } => process_tx_create_nonce(TxCreateNonceArgs {
+                keypair,
+                rpc_url,
+                authority,
+            })?,
+            TxCommand::Submit {
+                file,
+                keypair,
+                rpc_url,
+            } => process_tx_submit(TxSubmitArgs {
+                file,
+                keypair,
+                rpc_url,
+            })?,
+        },
         Commands::Upload {
             assets_dir,
             config,
@@ -515,10 +841,12 @@ async fn run() -> Result<()> {
         }
         Commands::Validate {
             assets_dir,
+            config,
             strict,
             skip_collection_prompt,
         } => process_validate(ValidateArgs {
             assets_dir,
+            config,
             strict,
             skip_collection_prompt,
         })?,
@@ -526,26 +854,41 @@ async fn run() -> Result<()> {
             keypair,
             rpc_url,
             cache,
-        } => process_verify(VerifyArgs {
-            keypair,
-            rpc_url,
-            cache,
-        })?,
+            deep,
+            report,
+        } => {
+            process_verify(VerifyArgs {
+                keypair,
+                rpc_url,
+                cache,
+                deep,
+                report,
+            })
+            .await?
+        }
         Commands::Withdraw {
             candy_machine,
             keypair,
             rpc_url,
+            cache,
             list,
             authority,
             priority_fee,
-        } => process_withdraw(WithdrawArgs {
-            candy_machine,
-            keypair,
-            rpc_url,
-            list,
-            authority,
-            priority_fee,
-        })?,
+        } => {
+            process_withdraw(WithdrawArgs {
+                candy_machine,
+                keypair,
+                rpc_url,
+                cache,
+                list,
+                authority,
+                priority_fee,
+                export_tx: export_tx.clone(),
+                nonce,
+                discovery,
+            })
+            .await?
+        }
         Commands::Sign {
             keypair,
             rpc_url,
@@ -559,10 +902,31 @@ async fn run() -> Result<()> {
                 cache,
                 mint,
                 candy_machine_id,
+                discovery,
             })
             .await?
         }
     }
 
+    if let Some(export_tx) = export_tx {
+        export_tx.write()?;
+    }
+
     Ok(())
 }
+
+/// Returns whether the command can export its transactions with --export-tx.
+fn supports_tx_export(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Collection {
+            command: CollectionSubcommands::Set { .. }
+        } | Commands::Config {
+            command: ConfigSubcommands::Update { .. }
+        } | Commands::Freeze {
+            command: FreezeCommand::UnlockFunds { .. }
+        } | Commands::Guard {
+            command: GuardCommand::Update { .. }
+        } | Commands::Withdraw { .. }
+    )
+}
@@ -1,10 +1,16 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    ops::Deref,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anchor_client::solana_sdk::{
     compute_budget::ComputeBudgetInstruction,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
     system_program, sysvar,
+    transaction::{Transaction, TransactionError},
 };
 use anyhow::Result;
 use borsh::BorshDeserialize;
@@ -20,16 +26,28 @@ use mpl_token_metadata::{
     },
     state::Metadata,
 };
-use solana_client::rpc_response::Response;
+use solana_client::{
+    client_error::ClientError, nonblocking::rpc_client::RpcClient, rpc_response::Response,
+};
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::ID as TOKEN_PROGRAM_ID;
 use tokio::sync::Semaphore;
 
 use crate::{
+    airdrop::{
+        structs::{
+            AirDropResults, PendingTransaction, RecipientInfo, SerdePubkey, TransactionResult,
+        },
+        utils::{
+            append_pending_transaction, clear_pending_transactions, merge_pending_transactions,
+            read_airdrop_results, write_airdrop_results,
+        },
+    },
     cache::load_cache,
     candy_machine::{CANDY_MACHINE_ID, *},
     common::*,
     config::{Cluster, SugarConfig},
+    mints::{mint_registry_path, record_mint, MintRecord},
     pdas::*,
     utils::*,
 };
@@ -42,8 +60,17 @@ pub struct MintArgs {
     pub receiver: Option<String>,
     pub candy_machine: Option<String>,
     pub priority_fee: u64,
+    pub concurrency: usize,
+    pub retries: u32,
+    pub results: Option<String>,
 }
 
+/// Delay before the first retry of a mint, doubled on each retry.
+const RETRY_BASE_DELAY_MS: u64 = 500;
+
+/// Maximum delay between retries of a mint.
+const RETRY_MAX_DELAY_MS: u64 = 30_000;
+
 pub async fn process_mint(args: MintArgs) -> Result<()> {
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
     let client = setup_client(&sugar_config)?;
@@ -99,10 +126,54 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
     println!("\nMinting to {}", &receiver_pubkey);
 
     let number = args.number.unwrap_or(1);
+
+    // mints recorded as completed in the results file are skipped
+    let mut results = match &args.results {
+        Some(path) => read_airdrop_results(path)?,
+        None => AirDropResults::new(),
+    };
+
+    // transactions sent by an interrupted run are considered as sent until
+    // they are reconciled, to avoid minting twice
+    if let Some(path) = &args.results {
+        let pending = merge_pending_transactions(&mut results, path)?;
+
+        if pending > 0 {
+            println!(
+                "{}{} {} transaction(s) of a previous run need to be reconciled (sugar airdrop reconcile --results {})",
+                WARNING_EMOJI,
+                style("warning:").yellow().bold(),
+                pending,
+                path
+            );
+        }
+    }
+    let completed = results
+        .get(&SerdePubkey::new(receiver_pubkey))
+        .map(|transactions| {
+            transactions
+                .iter()
+                .filter(|transaction| transaction.status)
+                .count() as u64
+        })
+        .unwrap_or(0)
+        .min(number);
+
+    if let (true, Some(path)) = (completed > 0, &args.results) {
+        println!("Skipping {completed} mints due to existing transactions in {path}");
+    }
+
+    let remaining = number - completed;
+
+    if remaining == 0 && number > 0 {
+        println!("{}", style("All items were already minted.").bold());
+        return Ok(());
+    }
+
     let available = candy_machine_state.data.items_available - candy_machine_state.items_redeemed;
 
-    if number > available || number == 0 {
-        let error = anyhow!("{} item(s) available, requested {}", available, number);
+    if remaining > available || number == 0 {
+        let error = anyhow!("{} item(s) available, requested {}", available, remaining);
         error!("{:?}", error);
         return Err(error);
     }
@@ -110,7 +181,10 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
     info!("Minting NFT from candy machine: {}", &candy_machine_id);
     info!("Candy machine program id: {:?}", CANDY_MACHINE_ID);
 
-    if number == 1 {
+    let results = Arc::new(Mutex::new(results));
+    let registry = mint_registry_path(&args.cache);
+
+    if remaining == 1 {
         let pb = spinner_with_style();
         pb.set_message(format!(
             "{} item(s) remaining",
@@ -118,16 +192,24 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
         ));
         let config = Arc::new(sugar_config);
 
-        let result = match mint(
+        let result = mint_with_retries(
             Arc::clone(&config),
             candy_pubkey,
             Arc::clone(&candy_machine_state),
             collection_update_authority,
             receiver_pubkey,
             args.priority_fee,
+            args.retries,
+            args.results.as_deref(),
         )
-        .await
-        {
+        .await;
+        record_mint_result(&results, &registry, &candy_pubkey, receiver_pubkey, &result);
+
+        if let Some(path) = &args.results {
+            write_mint_results(&results.lock().unwrap(), path)?;
+        }
+
+        let result = match result {
             Ok((signature, mint)) => {
                 println!("Mint: {mint}");
                 println!("Signature: {signature}");
@@ -142,30 +224,36 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
 
         pb.finish_with_message(result);
     } else {
-        let pb = progress_bar_with_style(number);
+        let pb = progress_bar_with_style(remaining);
 
         let mut tasks = Vec::new();
-        let semaphore = Arc::new(Semaphore::new(10));
+        let semaphore = Arc::new(Semaphore::new(args.concurrency.max(1)));
         let config = Arc::new(sugar_config);
 
-        for _i in 0..number {
+        for _i in 0..remaining {
             let config = config.clone();
             let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
             let candy_machine_state = candy_machine_state.clone();
+            let results = results.clone();
+            let registry = registry.clone();
+            let results_path = args.results.clone();
             let pb = pb.clone();
 
             // Start tasks
             tasks.push(tokio::spawn(async move {
                 let _permit = permit;
-                let res = mint(
+                let res = mint_with_retries(
                     config,
                     candy_pubkey,
                     candy_machine_state,
                     collection_update_authority,
                     receiver_pubkey,
                     args.priority_fee,
+                    args.retries,
+                    results_path.as_deref(),
                 )
                 .await;
+                record_mint_result(&results, &registry, &candy_pubkey, receiver_pubkey, &res);
                 pb.inc(1);
                 res
             }));
@@ -182,6 +270,10 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
             }
         }
 
+        if let Some(path) = &args.results {
+            write_mint_results(&results.lock().unwrap(), path)?;
+        }
+
         if error_count > 0 {
             pb.abandon_with_message(format!(
                 "{} {} items failed.",
@@ -191,8 +283,8 @@ pub async fn process_mint(args: MintArgs) -> Result<()> {
             return Err(anyhow!(
                 "{} {}/{} {}",
                 style("Minted").red().bold(),
-                number - error_count,
-                number,
+                remaining - error_count,
+                remaining,
                 style("of the items").red().bold()
             ));
         }
@@ -210,8 +302,250 @@ pub async fn mint(
     receiver: Pubkey,
     priority_fee: u64,
 ) -> Result<(Signature, Pubkey)> {
-    let client = setup_client(&config)?;
+    mint_with_retries(
+        config,
+        candy_machine_id,
+        candy_machine_state,
+        collection_update_authority,
+        receiver,
+        priority_fee,
+        0,
+        None,
+    )
+    .await
+}
+
+/// Mints an NFT, retrying with exponential backoff (and a new blockhash) when
+/// the error is retryable. When a results file is specified, each transaction
+/// is recorded in its journal before it is sent.
+#[allow(clippy::too_many_arguments)]
+pub async fn mint_with_retries(
+    config: Arc<SugarConfig>,
+    candy_machine_id: Pubkey,
+    candy_machine_state: Arc<CandyMachine>,
+    collection_update_authority: Pubkey,
+    receiver: Pubkey,
+    priority_fee: u64,
+    retries: u32,
+    results_path: Option<&str>,
+) -> Result<(Signature, Pubkey)> {
+    let mut attempt = 0;
+
+    loop {
+        // the transaction is built and sent with the blocking client
+        let sent = {
+            let config = Arc::clone(&config);
+            let candy_machine_state = Arc::clone(&candy_machine_state);
+            let results_path = results_path.map(str::to_string);
+
+            tokio::task::spawn_blocking(move || {
+                send_mint(
+                    &config,
+                    candy_machine_id,
+                    &candy_machine_state,
+                    collection_update_authority,
+                    receiver,
+                    priority_fee,
+                    results_path.as_deref(),
+                )
+            })
+            .await?
+        };
+
+        let result = match sent {
+            Ok((_, nft_mint, Ok(signature))) => Ok((signature, nft_mint)),
+            // the transaction might still land, so it must not be sent again
+            Ok((tx, nft_mint, Err(err))) if is_retryable_error(&err) => {
+                let rpc_client = RpcClient::new_with_commitment(
+                    config.rpc_url.clone(),
+                    CommitmentConfig::confirmed(),
+                );
+
+                if wait_for_transaction(&rpc_client, &tx).await? {
+                    Ok((tx.signatures[0], nft_mint))
+                } else {
+                    Err(err)
+                }
+            }
+            Ok((_, _, Err(err))) | Err(err) => Err(err),
+        };
+
+        match result {
+            Err(err) if attempt < retries && is_retryable_error(&err) => {
+                let delay = retry_delay(attempt);
+                info!(
+                    "Mint attempt {} failed, retrying in {}ms: {}",
+                    attempt + 1,
+                    delay,
+                    err
+                );
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns the delay (in milliseconds) before the retry of the given attempt.
+fn retry_delay(attempt: u32) -> u64 {
+    2u64.checked_pow(attempt)
+        .map_or(RETRY_MAX_DELAY_MS, |factor| {
+            RETRY_BASE_DELAY_MS.saturating_mul(factor)
+        })
+        .min(RETRY_MAX_DELAY_MS)
+}
+
+/// Builds and sends a mint transaction, recording it in the journal of the
+/// results file (if specified) before it is sent. Returns the transaction and
+/// the NFT mint together with the outcome of the send.
+fn send_mint(
+    config: &SugarConfig,
+    candy_machine_id: Pubkey,
+    candy_machine_state: &CandyMachine,
+    collection_update_authority: Pubkey,
+    receiver: Pubkey,
+    priority_fee: u64,
+    results_path: Option<&str>,
+) -> Result<(Transaction, Pubkey, Result<Signature>)> {
+    let client = setup_client(config)?;
     let program = client.program(CANDY_MACHINE_ID);
+
+    let (tx, nft_mint) = build_mint_transaction(
+        &program,
+        candy_machine_id,
+        candy_machine_state,
+        collection_update_authority,
+        receiver,
+        priority_fee,
+    )?;
+
+    if let Some(results_path) = results_path {
+        append_pending_transaction(
+            results_path,
+            &PendingTransaction {
+                address: SerdePubkey::new(receiver),
+                signature: tx.signatures[0].to_string(),
+                mint: nft_mint.to_string(),
+                blockhash: tx.message.recent_blockhash.to_string(),
+                recipient: RecipientInfo::default(),
+            },
+        )?;
+    }
+
+    let result = send_mint_transaction(&program, &tx, &nft_mint);
+
+    Ok((tx, nft_mint, result))
+}
+
+/// Returns whether the error is transient, i.e., the mint can be retried.
+fn is_retryable_error(err: &anyhow::Error) -> bool {
+    let client_error = match err.downcast_ref::<anchor_client::ClientError>() {
+        Some(anchor_client::ClientError::SolanaClientError(err)) => Some(err),
+        Some(_) => None,
+        None => err.downcast_ref::<ClientError>(),
+    };
+
+    match client_error {
+        Some(err) => matches!(
+            err.get_transaction_error(),
+            None | Some(TransactionError::BlockhashNotFound)
+        ),
+        // other errors (e.g., bot tax) are not transient
+        None => false,
+    }
+}
+
+/// Waits until the transaction is confirmed or its blockhash expires, returning
+/// whether it was confirmed.
+async fn wait_for_transaction(rpc_client: &RpcClient, tx: &Transaction) -> Result<bool> {
+    let signature = tx.signatures[0];
+
+    loop {
+        if let Some(status) = rpc_client.get_signature_status(&signature).await? {
+            return Ok(status.is_ok());
+        }
+
+        if !rpc_client
+            .is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::processed())
+            .await?
+        {
+            // the transaction might have landed right before the blockhash expired
+            return Ok(matches!(
+                rpc_client.get_signature_status(&signature).await?,
+                Some(Ok(()))
+            ));
+        }
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
+
+/// Writes the results file, which then includes every transaction recorded in
+/// the journal.
+fn write_mint_results(results: &AirDropResults, path: &str) -> Result<()> {
+    write_airdrop_results(results, path)?;
+    clear_pending_transactions(path)
+}
+
+/// Records the outcome of a mint in the results.
+fn record_mint_result(
+    results: &Mutex<AirDropResults>,
+    registry: &Path,
+    candy_machine: &Pubkey,
+    receiver: Pubkey,
+    result: &Result<(Signature, Pubkey)>,
+) {
+    let transaction = match result {
+        Ok((signature, mint)) => {
+            push_output(
+                "mints",
+                json!({ "mint": mint.to_string(), "signature": signature.to_string() }),
+            );
+            record_mint(
+                registry,
+                &MintRecord::new(candy_machine, mint, &receiver, signature, "mint"),
+            );
+            TransactionResult {
+                signature: signature.to_string(),
+                status: true,
+                mint: Some(mint.to_string()),
+                blockhash: None,
+                unconfirmed: false,
+                error: None,
+                recipient: RecipientInfo::default(),
+            }
+        }
+        Err(err) => TransactionResult {
+            signature: err.to_string(),
+            status: false,
+            mint: None,
+            blockhash: None,
+            unconfirmed: false,
+            error: Some(err.to_string()),
+            recipient: RecipientInfo::default(),
+        },
+    };
+
+    results
+        .lock()
+        .unwrap()
+        .entry(SerdePubkey::new(receiver))
+        .or_default()
+        .push(transaction);
+}
+
+/// Builds and signs the mint transaction, returning it together with the
+/// address of the NFT mint. The signature of the transaction is known before it
+/// is sent.
+pub fn build_mint_transaction<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    candy_machine_id: Pubkey,
+    candy_machine_state: &CandyMachine,
+    collection_update_authority: Pubkey,
+    receiver: Pubkey,
+    priority_fee: u64,
+) -> Result<(Transaction, Pubkey)> {
     let payer = program.payer();
 
     if candy_machine_state.mint_authority != payer {
@@ -308,7 +642,17 @@ pub async fn mint(
         .instruction(mint_ix[0].clone())
         .signer(&nft_mint);
 
-    let sig = builder.send()?;
+    Ok((builder.signed_transaction()?, nft_mint.pubkey()))
+}
+
+/// Sends a mint transaction, checking that the NFT was minted.
+pub fn send_mint_transaction<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    tx: &Transaction,
+    nft_mint: &Pubkey,
+) -> Result<Signature> {
+    let sig = program.rpc().send_and_confirm_transaction(tx)?;
+    let metadata_pda = find_metadata_pda(nft_mint);
 
     if let Err(_) | Ok(Response { value: None, .. }) = program
         .rpc()
@@ -327,5 +671,35 @@ pub async fn mint(
 
     info!("Minted! TxId: {}", sig);
 
-    Ok((sig, nft_mint.pubkey()))
+    Ok(sig)
+}
+
+#[test]
+fn test_is_retryable_error() {
+    let timeout = ClientError::from(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "timed out",
+    ));
+    assert!(is_retryable_error(&timeout.into()));
+
+    let expired = ClientError::from(TransactionError::BlockhashNotFound);
+    assert!(is_retryable_error(&expired.into()));
+
+    let failed = ClientError::from(TransactionError::InsufficientFundsForFee);
+    assert!(!is_retryable_error(&failed.into()));
+
+    let anchor = anchor_client::ClientError::SolanaClientError(ClientError::from(
+        TransactionError::BlockhashNotFound,
+    ));
+    assert!(is_retryable_error(&anchor.into()));
+
+    assert!(!is_retryable_error(&anyhow!("bot tax")));
+}
+
+#[test]
+fn test_retry_delay() {
+    assert_eq!(retry_delay(0), RETRY_BASE_DELAY_MS);
+    assert_eq!(retry_delay(3), 8 * RETRY_BASE_DELAY_MS);
+    assert_eq!(retry_delay(10), RETRY_MAX_DELAY_MS);
+    assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY_MS);
 }
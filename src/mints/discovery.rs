@@ -0,0 +1,144 @@
+use std::{fmt, future::Future, str::FromStr, sync::Arc};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::{anyhow, Result};
+use console::style;
+use solana_client::rpc_client::RpcClient;
+
+use crate::{
+    config::Cluster,
+    constants::WARNING_EMOJI,
+    mints::{
+        get_cm_history_mint_accounts, get_registry_mint_accounts, mint_history_cache_path,
+        mint_registry_path,
+    },
+    pdas::find_candy_machine_creator_pda,
+    utils::get_cm_creator_mint_accounts,
+};
+
+/// How the mints of a candy machine (and the candy machines of an authority)
+/// are found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MintDiscovery {
+    /// Uses `getProgramAccounts`, falling back to the transaction history if the
+    /// call fails or is not supported.
+    #[default]
+    Auto,
+    /// Uses `getProgramAccounts` filters (e.g., the candy machine creator).
+    ProgramAccounts,
+    /// Uses the transaction history, cached locally for incremental re-runs.
+    History,
+    /// Uses the mint registry recorded by the mint and airdrop commands.
+    Registry,
+}
+
+impl fmt::Display for MintDiscovery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MintDiscovery::Auto => write!(f, "auto"),
+            MintDiscovery::ProgramAccounts => write!(f, "program-accounts"),
+            MintDiscovery::History => write!(f, "history"),
+            MintDiscovery::Registry => write!(f, "registry"),
+        }
+    }
+}
+
+impl FromStr for MintDiscovery {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(MintDiscovery::Auto),
+            "program-accounts" => Ok(MintDiscovery::ProgramAccounts),
+            "history" => Ok(MintDiscovery::History),
+            "registry" => Ok(MintDiscovery::Registry),
+            _ => Err(anyhow!(
+                "Invalid mint discovery: {s} (expected 'auto', 'program-accounts', 'history' \
+                or 'registry')"
+            )),
+        }
+    }
+}
+
+/// Runs the `getProgramAccounts` search or the transaction history scan,
+/// depending on the discovery method. The search is `None` when the cluster
+/// does not support it.
+pub async fn discover<T, S, H>(discovery: MintDiscovery, search: Option<S>, history: H) -> Result<T>
+where
+    S: FnOnce() -> Result<T>,
+    H: Future<Output = Result<T>>,
+{
+    match (discovery, search) {
+        (MintDiscovery::History, _) => history.await,
+        (MintDiscovery::Registry, _) => Err(anyhow!(
+            "The mint registry only records minted NFTs, use another mint discovery"
+        )),
+        (MintDiscovery::ProgramAccounts, Some(search)) => search(),
+        (MintDiscovery::ProgramAccounts, None) => Err(anyhow!(
+            "Cluster being used is unsupported for this command."
+        )),
+        (MintDiscovery::Auto, Some(search)) => match search() {
+            Ok(result) => Ok(result),
+            Err(err) => {
+                println!(
+                    "{}{} getProgramAccounts failed ({}), using the transaction history",
+                    WARNING_EMOJI,
+                    style("warning:").yellow().bold(),
+                    err
+                );
+                history.await
+            }
+        },
+        (MintDiscovery::Auto, None) => history.await,
+    }
+}
+
+/// Returns the mints of the candy machine. The mint registry and the history
+/// cache are kept in the directory of the cache file.
+pub async fn get_candy_machine_mints(
+    client: Arc<RpcClient>,
+    candy_machine: &Pubkey,
+    cluster: &Cluster,
+    discovery: MintDiscovery,
+    cache: &str,
+) -> Result<Vec<Pubkey>> {
+    if discovery == MintDiscovery::Registry {
+        return get_registry_mint_accounts(&mint_registry_path(cache), candy_machine);
+    }
+
+    let search = match cluster {
+        Cluster::Mainnet | Cluster::Devnet | Cluster::Localnet => Some(|| {
+            let (creator, _) = find_candy_machine_creator_pda(candy_machine);
+            get_cm_creator_mint_accounts(&client, &creator.to_string(), 0)
+        }),
+        _ => None,
+    };
+
+    discover(
+        discovery,
+        search,
+        get_cm_history_mint_accounts(
+            client.clone(),
+            candy_machine,
+            &mint_history_cache_path(cache),
+        ),
+    )
+    .await
+}
+
+#[test]
+fn test_mint_discovery_from_str() {
+    for discovery in [
+        MintDiscovery::Auto,
+        MintDiscovery::ProgramAccounts,
+        MintDiscovery::History,
+        MintDiscovery::Registry,
+    ] {
+        assert_eq!(
+            MintDiscovery::from_str(&discovery.to_string()).unwrap(),
+            discovery
+        );
+    }
+
+    assert!(MintDiscovery::from_str("creator").is_err());
+}
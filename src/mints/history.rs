@@ -0,0 +1,525 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use anchor_client::solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, Result};
+use mpl_candy_machine_core::CandyMachine;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiLoadedAddresses, UiMessage,
+    UiTransactionEncoding, UiTransactionTokenBalance,
+};
+use tokio::sync::Semaphore;
+use tracing::{error, warn};
+
+use crate::{
+    candy_machine::CANDY_MACHINE_ID, constants::MINT_HISTORY_CACHE_FILE,
+    utils::progress_bar_with_style,
+};
+
+// maximum number of signatures returned by each getSignaturesForAddress request
+const SIGNATURES_LIMIT: usize = 1000;
+
+// number of transactions loaded in parallel
+const HISTORY_CONCURRENCY: usize = 20;
+
+/// Maximum number of accounts per `getMultipleAccounts` request.
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+// offset of the authority in the candy machine account data
+const AUTHORITY_OFFSET: usize = 16;
+
+/// Accounts found in the transaction history of each address, so a re-run only
+/// needs to load the transactions sent after the last one processed.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCache {
+    /// Mints found for each candy machine.
+    #[serde(default)]
+    pub mints: HashMap<String, HistoryEntry>,
+    /// Candy machines found for each authority.
+    #[serde(default)]
+    pub candy_machines: HashMap<String, HistoryEntry>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub last_signature: Option<String>,
+    pub accounts: Vec<String>,
+}
+
+/// Returns the path of the mint history cache, kept in the directory of the cache
+/// file.
+pub fn mint_history_cache_path(cache: &str) -> PathBuf {
+    Path::new(cache)
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(MINT_HISTORY_CACHE_FILE)
+}
+
+impl HistoryCache {
+    /// Loads the history cache, starting a new one if the file does not exist or
+    /// cannot be read.
+    pub fn load(path: &Path) -> HistoryCache {
+        if !path.exists() {
+            return HistoryCache::default();
+        }
+
+        match fs::read_to_string(path)
+            .map_err(|err| anyhow!(err))
+            .and_then(|content| serde_json::from_str(&content).map_err(|err| anyhow!(err)))
+        {
+            Ok(cache) => cache,
+            Err(err) => {
+                warn!("Ignoring invalid mint history cache: {}", err);
+                HistoryCache::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).map_err(|err| {
+            anyhow!(
+                "Failed to write mint history cache {}: {}",
+                path.display(),
+                err
+            )
+        })
+    }
+}
+
+impl HistoryEntry {
+    fn accounts(&self) -> Result<Vec<Pubkey>> {
+        self.accounts
+            .iter()
+            .map(|account| {
+                Pubkey::from_str(account)
+                    .map_err(|_| anyhow!("Invalid pubkey in the mint history cache: {}", account))
+            })
+            .collect()
+    }
+
+    fn add_accounts(&mut self, accounts: &[Pubkey], last_signature: Option<Signature>) {
+        let mut seen = self.accounts.iter().cloned().collect::<HashSet<_>>();
+
+        for account in accounts {
+            let account = account.to_string();
+            if seen.insert(account.clone()) {
+                self.accounts.push(account);
+            }
+        }
+
+        if let Some(signature) = last_signature {
+            self.last_signature = Some(signature.to_string());
+        }
+    }
+}
+
+/// Finds the NFTs minted from a candy machine by going through the (successful)
+/// transactions of the candy machine account. This does not depend on the
+/// `getProgramAccounts` call, which is not supported by every RPC provider.
+pub async fn get_cm_history_mint_accounts(
+    client: Arc<RpcClient>,
+    candy_machine: &Pubkey,
+    history_cache: &Path,
+) -> Result<Vec<Pubkey>> {
+    let mut cache = HistoryCache::load(history_cache);
+    let mut entry = cache
+        .mints
+        .get(&candy_machine.to_string())
+        .cloned()
+        .unwrap_or_default();
+
+    let (mints, last_signature) =
+        scan_history(client, candy_machine, &entry, decode_minted_mints).await?;
+
+    entry.add_accounts(&mints, last_signature);
+    let mints = entry.accounts()?;
+
+    cache.mints.insert(candy_machine.to_string(), entry);
+    cache.save(history_cache)?;
+
+    Ok(mints)
+}
+
+/// Finds the candy machines of an authority by going through the (successful)
+/// transactions of the authority that used the candy machine program. Returns the
+/// candy machines that still exist and have the authority.
+pub async fn get_authority_history_candy_machines(
+    client: Arc<RpcClient>,
+    authority: &Pubkey,
+    history_cache: &Path,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let mut cache = HistoryCache::load(history_cache);
+    let mut entry = cache
+        .candy_machines
+        .get(&authority.to_string())
+        .cloned()
+        .unwrap_or_default();
+
+    let (candidates, last_signature) =
+        scan_history(client.clone(), authority, &entry, decode_program_accounts).await?;
+
+    // only the accounts that are candy machines are kept in the cache
+    let candy_machines = get_candy_machine_accounts(&client, &candidates)?
+        .into_iter()
+        .map(|(pubkey, _)| pubkey)
+        .collect::<Vec<_>>();
+
+    entry.add_accounts(&candy_machines, last_signature);
+    let candy_machines = get_candy_machine_accounts(&client, &entry.accounts()?)?
+        .into_iter()
+        .filter(|(_, account)| {
+            account.data[AUTHORITY_OFFSET..AUTHORITY_OFFSET + 32] == authority.to_bytes()
+        })
+        .collect();
+
+    cache.candy_machines.insert(authority.to_string(), entry);
+    cache.save(history_cache)?;
+
+    Ok(candy_machines)
+}
+
+/// Loads the transactions of the address sent after the last signature of the
+/// entry, returning the accounts decoded from them (in transaction order) and
+/// the most recent signature.
+async fn scan_history(
+    client: Arc<RpcClient>,
+    address: &Pubkey,
+    entry: &HistoryEntry,
+    decode: fn(&EncodedConfirmedTransactionWithStatusMeta) -> Vec<Pubkey>,
+) -> Result<(Vec<Pubkey>, Option<Signature>)> {
+    let until = match &entry.last_signature {
+        Some(signature) => Some(Signature::from_str(signature)?),
+        None => None,
+    };
+    let (signatures, last_signature) = get_signatures(&client, address, until)?;
+
+    let pb = progress_bar_with_style(signatures.len() as u64);
+    pb.set_message("Loading transactions...");
+
+    let semaphore = Arc::new(Semaphore::new(HISTORY_CONCURRENCY));
+    let accounts = Arc::new(Mutex::new(Vec::new()));
+    let failed = Arc::new(Mutex::new(0usize));
+    let mut tasks = Vec::new();
+
+    for (position, signature) in signatures.into_iter().enumerate() {
+        let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+        let client = client.clone();
+        let accounts = accounts.clone();
+        let failed = failed.clone();
+        let pb = pb.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = permit;
+
+            let config = RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            };
+
+            match client.get_transaction_with_config(&signature, config) {
+                Ok(transaction) => {
+                    accounts
+                        .lock()
+                        .unwrap()
+                        .push((position, decode(&transaction)));
+                }
+                Err(err) => {
+                    error!("Failed to load transaction {}: {}", signature, err);
+                    *failed.lock().unwrap() += 1;
+                }
+            }
+
+            pb.inc(1);
+        }));
+    }
+
+    for task in tasks {
+        task.await?;
+    }
+
+    pb.finish_and_clear();
+
+    let failed = *failed.lock().unwrap();
+
+    if failed > 0 {
+        return Err(anyhow!(
+            "Failed to load {} transaction(s) of {}",
+            failed,
+            address
+        ));
+    }
+
+    let mut accounts = accounts.lock().unwrap().clone();
+    accounts.sort_by_key(|(position, _)| *position);
+
+    Ok((
+        accounts
+            .into_iter()
+            .flat_map(|(_, accounts)| accounts)
+            .collect(),
+        last_signature,
+    ))
+}
+
+/// Returns the signatures of the successful transactions of the account sent
+/// after the `until` signature, from the oldest to the most recent, and the most
+/// recent signature.
+fn get_signatures(
+    client: &RpcClient,
+    address: &Pubkey,
+    until: Option<Signature>,
+) -> Result<(Vec<Signature>, Option<Signature>)> {
+    let mut signatures = Vec::new();
+    let mut last_signature = None;
+    let mut before = None;
+
+    loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until,
+            limit: Some(SIGNATURES_LIMIT),
+            commitment: Some(CommitmentConfig::confirmed()),
+        };
+        let page = client.get_signatures_for_address_with_config(address, config)?;
+
+        let last = match page.last() {
+            Some(last) => Signature::from_str(&last.signature)?,
+            None => break,
+        };
+
+        if last_signature.is_none() {
+            last_signature = Some(Signature::from_str(&page[0].signature)?);
+        }
+
+        for status in &page {
+            if status.err.is_none() {
+                signatures.push(Signature::from_str(&status.signature)?);
+            }
+        }
+
+        if page.len() < SIGNATURES_LIMIT {
+            break;
+        }
+
+        before = Some(last);
+    }
+
+    signatures.reverse();
+
+    Ok((signatures, last_signature))
+}
+
+/// Loads the accounts and returns the ones that are candy machines.
+fn get_candy_machine_accounts(
+    client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<(Pubkey, Account)>> {
+    let mut candy_machines = Vec::new();
+
+    for chunk in pubkeys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let accounts = client.get_multiple_accounts(chunk)?;
+
+        for (pubkey, account) in chunk.iter().zip(accounts) {
+            if let Some(account) = account.filter(|account| {
+                account.owner == CANDY_MACHINE_ID
+                    && account.data.len() >= AUTHORITY_OFFSET + 32
+                    && account.data[..8] == CandyMachine::discriminator()
+            }) {
+                candy_machines.push((*pubkey, account));
+            }
+        }
+    }
+
+    Ok(candy_machines)
+}
+
+fn decode_minted_mints(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<Pubkey> {
+    match &transaction.transaction.meta {
+        Some(meta) => {
+            let pre_balances: Option<Vec<_>> = meta.pre_token_balances.clone().into();
+            let post_balances: Option<Vec<_>> = meta.post_token_balances.clone().into();
+
+            find_minted_mints(
+                &pre_balances.unwrap_or_default(),
+                &post_balances.unwrap_or_default(),
+            )
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Returns the accounts of a transaction that used the candy machine program,
+/// including the accounts loaded from address lookup tables.
+fn decode_program_accounts(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<Pubkey> {
+    let mut account_keys = match &transaction.transaction.transaction {
+        EncodedTransaction::Json(transaction) => match &transaction.message {
+            UiMessage::Raw(message) => message.account_keys.clone(),
+            UiMessage::Parsed(_) => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+
+    if let Some(meta) = &transaction.transaction.meta {
+        let loaded_addresses: Option<UiLoadedAddresses> = meta.loaded_addresses.clone().into();
+
+        if let Some(loaded_addresses) = loaded_addresses {
+            account_keys.extend(loaded_addresses.writable);
+            account_keys.extend(loaded_addresses.readonly);
+        }
+    }
+
+    let program_id = CANDY_MACHINE_ID.to_string();
+
+    if !account_keys.contains(&program_id) {
+        return Vec::new();
+    }
+
+    account_keys
+        .iter()
+        .filter(|key| **key != program_id)
+        .filter_map(|key| Pubkey::from_str(key).ok())
+        .collect()
+}
+
+/// Returns the NFT mints created by a transaction: mints with a token account
+/// holding a single token with no decimals after the transaction, which did not
+/// have a balance before.
+pub fn find_minted_mints(
+    pre_balances: &[UiTransactionTokenBalance],
+    post_balances: &[UiTransactionTokenBalance],
+) -> Vec<Pubkey> {
+    let existing = pre_balances
+        .iter()
+        .map(|balance| balance.mint.as_str())
+        .collect::<HashSet<_>>();
+
+    post_balances
+        .iter()
+        .filter(|balance| {
+            balance.ui_token_amount.amount == "1"
+                && balance.ui_token_amount.decimals == 0
+                && !existing.contains(balance.mint.as_str())
+        })
+        .filter_map(|balance| Pubkey::from_str(&balance.mint).ok())
+        .collect()
+}
+
+#[test]
+fn test_find_minted_mints() {
+    use serde_json::json;
+
+    let balance = |mint: &Pubkey, amount: &str, decimals: u8| -> UiTransactionTokenBalance {
+        serde_json::from_value(json!({
+            "accountIndex": 1,
+            "mint": mint.to_string(),
+            "uiTokenAmount": {
+                "uiAmount": null,
+                "decimals": decimals,
+                "amount": amount,
+                "uiAmountString": amount,
+            }
+        }))
+        .unwrap()
+    };
+
+    let nft = Pubkey::new_unique();
+    let payment = Pubkey::new_unique();
+    let fungible = Pubkey::new_unique();
+
+    let pre_balances = vec![balance(&payment, "10", 0)];
+    let post_balances = vec![
+        balance(&nft, "1", 0),
+        balance(&payment, "1", 0),
+        balance(&fungible, "1", 9),
+    ];
+
+    assert_eq!(find_minted_mints(&pre_balances, &post_balances), vec![nft]);
+    assert!(find_minted_mints(&post_balances, &post_balances).is_empty());
+}
+
+#[test]
+fn test_history_entry_add_accounts() {
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    let signature = Signature::new_unique();
+
+    let mut entry = HistoryEntry::default();
+    entry.add_accounts(&[first, second, first], Some(signature));
+    entry.add_accounts(&[second], None);
+
+    assert_eq!(entry.accounts().unwrap(), vec![first, second]);
+    assert_eq!(entry.last_signature, Some(signature.to_string()));
+}
+
+#[test]
+fn test_decode_program_accounts() {
+    use serde_json::json;
+
+    let authority = Pubkey::new_unique();
+    let candy_machine = Pubkey::new_unique();
+    let lookup_account = Pubkey::new_unique();
+
+    let transaction: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(json!({
+        "slot": 1,
+        "blockTime": null,
+        "version": 0,
+        "transaction": {
+            "signatures": [Signature::default().to_string()],
+            "message": {
+                "header": {
+                    "numRequiredSignatures": 1,
+                    "numReadonlySignedAccounts": 0,
+                    "numReadonlyUnsignedAccounts": 1,
+                },
+                "accountKeys": [authority.to_string(), CANDY_MACHINE_ID.to_string()],
+                "recentBlockhash": Pubkey::default().to_string(),
+                "instructions": [],
+            },
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "loadedAddresses": {
+                "writable": [candy_machine.to_string()],
+                "readonly": [lookup_account.to_string()],
+            },
+        },
+    }))
+    .unwrap();
+
+    assert_eq!(
+        decode_program_accounts(&transaction),
+        vec![authority, candy_machine, lookup_account]
+    );
+}
+
+#[test]
+fn test_mint_history_cache_path() {
+    assert_eq!(
+        mint_history_cache_path("assets/cache.json"),
+        Path::new("assets").join(MINT_HISTORY_CACHE_FILE)
+    );
+    assert_eq!(
+        mint_history_cache_path("cache.json"),
+        PathBuf::from(MINT_HISTORY_CACHE_FILE)
+    );
+}
@@ -0,0 +1,9 @@
+mod discovery;
+mod history;
+mod process;
+mod registry;
+
+pub use discovery::*;
+pub use history::*;
+pub use process::*;
+pub use registry::*;
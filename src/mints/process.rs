@@ -0,0 +1,310 @@
+use std::{fs, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use console::style;
+use mpl_candy_machine_core::ConfigLine;
+use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+
+use crate::{
+    cache::load_cache,
+    candy_machine::{get_config_lines, CANDY_MACHINE_ID},
+    common::*,
+    config::Cluster,
+    mints::{get_candy_machine_mints, MintDiscovery},
+    pdas::find_metadata_pda,
+    setup::get_rpc_url,
+    utils::*,
+};
+
+pub struct MintsArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub candy_machine: Option<String>,
+    pub file: String,
+    pub timeout: Option<u64>,
+    pub discovery: MintDiscovery,
+}
+
+/// NFT minted from a candy machine. The index is the position of the item in
+/// the candy machine, when it can be matched by its metadata uri.
+#[derive(Clone, Debug, Serialize)]
+pub struct MintItem {
+    pub mint: String,
+    pub metadata: String,
+    pub name: String,
+    pub uri: String,
+    pub index: Option<u32>,
+}
+
+// Timeout for the GPA call (in seconds).
+const DEFAULT_TIMEOUT: u64 = 300;
+
+/// Maximum number of accounts per `getMultipleAccounts` request.
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+pub async fn process_mints(args: MintsArgs) -> Result<()> {
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url.clone())?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    // the cache is only required when the candy machine id is not specified,
+    // otherwise it is used (if present) to match the index of the items
+
+    let cache = if args.candy_machine.is_none() || Path::new(&args.cache).exists() {
+        Some(load_cache(&args.cache, false)?)
+    } else {
+        None
+    };
+
+    // the candy machine id specified takes precedence over the one from the cache
+
+    let candy_machine_id = match (args.candy_machine, &cache) {
+        (Some(candy_machine_id), _) => candy_machine_id,
+        (None, Some(cache)) => cache.program.candy_machine.clone(),
+        (None, None) => unreachable!(),
+    };
+
+    let candy_pubkey = Pubkey::from_str(&candy_machine_id)
+        .map_err(|_| anyhow!("Failed to parse candy machine id: {}", candy_machine_id))?;
+
+    println!(
+        "{} {}Getting minted NFTs for candy machine {}",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        candy_machine_id
+    );
+
+    let spinner = spinner_with_style();
+    spinner.set_message("Loading...");
+
+    let solana_cluster: Cluster = get_cluster(program.rpc())?;
+    let rpc_url = get_rpc_url(args.rpc_url);
+
+    let solana_cluster = if rpc_url.ends_with("8899") {
+        Cluster::Localnet
+    } else {
+        solana_cluster
+    };
+
+    let client = Arc::new(RpcClient::new_with_timeout(
+        &rpc_url,
+        Duration::from_secs(args.timeout.unwrap_or(DEFAULT_TIMEOUT)),
+    ));
+
+    let mints = get_candy_machine_mints(
+        client.clone(),
+        &candy_pubkey,
+        &solana_cluster,
+        args.discovery,
+        &args.cache,
+    )
+    .await?;
+
+    spinner.finish_and_clear();
+
+    if mints.is_empty() {
+        println!("{}", style("No NFTs found.").red().bold());
+        return Err(anyhow!(
+            "No minted NFTs found for candy machine {}",
+            candy_machine_id
+        ));
+    }
+
+    println!("Found {} NFTs", mints.len());
+
+    println!(
+        "\n{} {}Loading metadata",
+        style("[2/3]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    // the config lines are not available once the candy machine is withdrawn
+
+    let config_lines = match client.get_account_data(&candy_pubkey) {
+        Ok(data) => {
+            let candy_machine = CandyMachine::try_deserialize(&mut data.as_slice())?;
+            get_config_lines(&candy_machine, &data)?
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let indices = get_item_indices(&config_lines, cache.as_ref());
+    let items = get_mint_items(&client, &mints, &indices)?;
+
+    println!(
+        "\n{} {}Writing mint list",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    write_mint_list(&items, &args.file)?;
+
+    let unmatched = items.iter().filter(|item| item.index.is_none()).count();
+
+    println!("{} {}", style("Minted:").bold(), items.len());
+    println!("{} {}", style("Mint list:").bold(), args.file);
+    set_output("mintList", &args.file);
+    set_output("minted", items.len());
+
+    if unmatched > 0 {
+        println!(
+            "\n{}{} {} NFT(s) without a matching item index",
+            WARNING_EMOJI,
+            style("warning:").yellow().bold(),
+            unmatched
+        );
+    }
+
+    Ok(())
+}
+
+/// Maps the metadata uri of each item to its index, using the config lines of the
+/// candy machine or the cache items. Uris shared by more than one item (e.g.,
+/// hidden settings before reveal) are not mapped.
+fn get_item_indices(
+    config_lines: &[Option<ConfigLine>],
+    cache: Option<&Cache>,
+) -> HashMap<String, Option<u32>> {
+    let mut uris = Vec::new();
+
+    if config_lines.iter().any(|line| line.is_some()) {
+        for (index, line) in config_lines.iter().enumerate() {
+            if let Some(line) = line {
+                uris.push((line.uri.clone(), index as u32));
+            }
+        }
+    } else if let Some(cache) = cache {
+        for (key, item) in cache.items.iter() {
+            if let Ok(index) = key.parse::<u32>() {
+                uris.push((item.metadata_link.clone(), index));
+            }
+        }
+    }
+
+    let mut indices = HashMap::new();
+
+    for (uri, index) in uris {
+        indices
+            .entry(uri)
+            .and_modify(|value| *value = None)
+            .or_insert(Some(index));
+    }
+
+    indices
+}
+
+/// Loads the metadata of each mint.
+fn get_mint_items(
+    client: &RpcClient,
+    mints: &[Pubkey],
+    indices: &HashMap<String, Option<u32>>,
+) -> Result<Vec<MintItem>> {
+    let pb = progress_bar_with_style(mints.len() as u64);
+    let mut items = Vec::with_capacity(mints.len());
+
+    for chunk in mints.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let metadata_pdas = chunk.iter().map(find_metadata_pda).collect::<Vec<_>>();
+        let accounts = client.get_multiple_accounts(&metadata_pdas)?;
+
+        for ((mint, metadata_pda), account) in chunk.iter().zip(metadata_pdas).zip(accounts) {
+            let (name, uri) = account
+                .and_then(|account| Metadata::safe_deserialize(&account.data).ok())
+                .map(|metadata| {
+                    (
+                        metadata.data.name.trim_matches(char::from(0)).to_string(),
+                        metadata.data.uri.trim_matches(char::from(0)).to_string(),
+                    )
+                })
+                .unwrap_or_default();
+
+            items.push(MintItem {
+                mint: mint.to_string(),
+                metadata: metadata_pda.to_string(),
+                index: indices.get(&uri).copied().flatten(),
+                name,
+                uri,
+            });
+        }
+
+        pb.inc(chunk.len() as u64);
+    }
+
+    pb.finish_and_clear();
+
+    // items with an index first, in index order
+    items.sort_by(|a, b| {
+        (a.index.is_none(), a.index, &a.mint).cmp(&(b.index.is_none(), b.index, &b.mint))
+    });
+
+    Ok(items)
+}
+
+/// Writes the mint list as CSV, JSON lines (.jsonl) or JSON (.json), depending
+/// on the file extension.
+fn write_mint_list(items: &[MintItem], path: &str) -> Result<()> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let content = match extension.as_deref() {
+        Some("json") => serde_json::to_string_pretty(items)?,
+        Some("jsonl") | Some("ndjson") => {
+            let mut lines = Vec::with_capacity(items.len());
+            for item in items {
+                lines.push(serde_json::to_string(item)?);
+            }
+            lines.join("\n")
+        }
+        _ => mint_items_to_csv(items),
+    };
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn mint_items_to_csv(items: &[MintItem]) -> String {
+    let mut csv = String::from("mint,metadata,name,uri,index\n");
+
+    for item in items {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            item.mint,
+            item.metadata,
+            escape_csv_value(&item.name),
+            escape_csv_value(&item.uri),
+            item.index
+                .map(|index| index.to_string())
+                .unwrap_or_default()
+        ));
+    }
+
+    csv
+}
+
+#[test]
+fn test_get_item_indices() {
+    let line = |name: &str, uri: &str| {
+        Some(ConfigLine {
+            name: name.to_string(),
+            uri: uri.to_string(),
+        })
+    };
+
+    let config_lines = vec![
+        line("Sugar #1", "https://arweave.net/1"),
+        None,
+        line("Sugar #3", "https://arweave.net/hidden"),
+        line("Sugar #4", "https://arweave.net/hidden"),
+    ];
+
+    let indices = get_item_indices(&config_lines, None);
+
+    assert_eq!(indices.get("https://arweave.net/1"), Some(&Some(0)));
+    assert_eq!(indices.get("https://arweave.net/hidden"), Some(&None));
+    assert_eq!(indices.len(), 2);
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
+use anyhow::{anyhow, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::constants::MINT_REGISTRY_FILE;
+
+/// NFT minted by sugar, recorded in the mint registry.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintRecord {
+    pub candy_machine: String,
+    pub mint: String,
+    pub receiver: String,
+    pub signature: String,
+    pub time: String,
+    /// Command that minted the NFT (`mint` or `airdrop`).
+    pub source: String,
+}
+
+impl MintRecord {
+    pub fn new(
+        candy_machine: &Pubkey,
+        mint: &Pubkey,
+        receiver: &Pubkey,
+        signature: &Signature,
+        source: &str,
+    ) -> MintRecord {
+        MintRecord {
+            candy_machine: candy_machine.to_string(),
+            mint: mint.to_string(),
+            receiver: receiver.to_string(),
+            signature: signature.to_string(),
+            time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Returns the path of the mint registry, kept in the directory of the cache file.
+pub fn mint_registry_path(cache: &str) -> PathBuf {
+    Path::new(cache)
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(MINT_REGISTRY_FILE)
+}
+
+/// Appends the minted NFT to the registry. The NFT is already minted, so a
+/// failure is only logged.
+pub fn record_mint(registry: &Path, record: &MintRecord) {
+    if let Err(err) = append_mint_record(registry, record) {
+        error!(
+            "Failed to record mint {} in {}: {}",
+            record.mint,
+            registry.display(),
+            err
+        );
+    }
+}
+
+fn append_mint_record(registry: &Path, record: &MintRecord) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(registry)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Reads the records of the mint registry.
+pub fn read_mint_registry(registry: &Path) -> Result<Vec<MintRecord>> {
+    let content = fs::read_to_string(registry).map_err(|err| {
+        anyhow!(
+            "Failed to read mint registry {}: {}",
+            registry.display(),
+            err
+        )
+    })?;
+
+    let mut records = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        records.push(serde_json::from_str(line).map_err(|err| {
+            anyhow!(
+                "Invalid record in mint registry {} (line {}): {}",
+                registry.display(),
+                index + 1,
+                err
+            )
+        })?);
+    }
+
+    Ok(records)
+}
+
+/// Returns the mints of the candy machine recorded in the registry.
+pub fn get_registry_mint_accounts(registry: &Path, candy_machine: &Pubkey) -> Result<Vec<Pubkey>> {
+    let candy_machine = candy_machine.to_string();
+    let mut seen = HashSet::new();
+    let mut mints = Vec::new();
+
+    for record in read_mint_registry(registry)? {
+        if record.candy_machine == candy_machine && seen.insert(record.mint.clone()) {
+            mints.push(
+                Pubkey::from_str(&record.mint)
+                    .map_err(|_| anyhow!("Invalid mint in mint registry: {}", record.mint))?,
+            );
+        }
+    }
+
+    Ok(mints)
+}
+
+#[test]
+fn test_mint_registry() {
+    let registry =
+        std::env::temp_dir().join(format!("sugar-mint-registry-{}.jsonl", std::process::id()));
+    let candy_machine = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let receiver = Pubkey::new_unique();
+    let signature = Signature::new_unique();
+
+    let record = MintRecord::new(&candy_machine, &mint, &receiver, &signature, "mint");
+    record_mint(&registry, &record);
+    record_mint(&registry, &record);
+    record_mint(
+        &registry,
+        &MintRecord::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &receiver,
+            &signature,
+            "airdrop",
+        ),
+    );
+
+    assert_eq!(read_mint_registry(&registry).unwrap()[0], record);
+    assert_eq!(
+        get_registry_mint_accounts(&registry, &candy_machine).unwrap(),
+        vec![mint]
+    );
+
+    fs::remove_file(&registry).unwrap();
+
+    assert_eq!(
+        mint_registry_path("assets/cache.json"),
+        Path::new("assets").join(MINT_REGISTRY_FILE)
+    );
+    assert_eq!(
+        mint_registry_path("cache.json"),
+        PathBuf::from(MINT_REGISTRY_FILE)
+    );
+}
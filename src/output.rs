@@ -0,0 +1,188 @@
+use std::{
+    fmt,
+    io::Write,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::parse::{parse_sugar_error_code, parse_sugar_errors};
+
+// whether the command output is JSON, so no other text is printed to stdout
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // result of the running command, printed in JSON mode
+    static ref RESULT: Mutex<Map<String, Value>> = Mutex::new(Map::new());
+    // original stdout, when the progress output is redirected to stderr
+    static ref STDOUT: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json)
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(anyhow!(
+                "Invalid output format: {s} (expected 'text' or 'json')"
+            )),
+        }
+    }
+}
+
+/// Sets the output format of the running command. In JSON mode, everything
+/// printed to stdout is redirected to stderr, so the result object is the only
+/// content of stdout.
+pub fn set_output_format(format: OutputFormat) -> Result<()> {
+    if format.is_json() {
+        *STDOUT.lock().unwrap() = Some(redirect_stdout()?);
+    }
+
+    JSON_OUTPUT.store(format.is_json(), Ordering::SeqCst);
+
+    Ok(())
+}
+
+/// Returns whether the running command prints JSON output.
+pub fn is_json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::SeqCst)
+}
+
+/// Sets a field of the command result.
+pub fn set_output<T: Serialize>(key: &str, value: T) {
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    RESULT.lock().unwrap().insert(key.to_string(), value);
+}
+
+/// Appends a value to an array field of the command result.
+pub fn push_output<T: Serialize>(key: &str, value: T) {
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    let mut result = RESULT.lock().unwrap();
+
+    match result.get_mut(key) {
+        Some(Value::Array(values)) => values.push(value),
+        _ => {
+            result.insert(key.to_string(), Value::Array(vec![value]));
+        }
+    }
+}
+
+/// Adds the fields of the (serialized) value to the command result.
+pub fn merge_output<T: Serialize>(value: &T) -> Result<()> {
+    match serde_json::to_value(value)? {
+        Value::Object(fields) => {
+            RESULT.lock().unwrap().extend(fields);
+            Ok(())
+        }
+        _ => Err(anyhow!("Command output must be an object")),
+    }
+}
+
+/// Prints the result of the command as JSON to stdout.
+pub fn print_output() -> Result<()> {
+    let result = Value::Object(RESULT.lock().unwrap().clone());
+    write_json(&result)
+}
+
+/// Prints the error of the command as JSON to stdout.
+pub fn print_error_output(err: &anyhow::Error) -> Result<()> {
+    let message = err.to_string();
+    let error = json!({
+        "error": {
+            "code": parse_sugar_error_code(&message),
+            "message": parse_sugar_errors(&message),
+        }
+    });
+    write_json(&error)
+}
+
+fn write_json(value: &Value) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+
+    match STDOUT.lock().unwrap().as_mut() {
+        Some(stdout) => {
+            writeln!(stdout, "{json}")?;
+            stdout.flush()?;
+        }
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
+
+/// Points stdout to stderr and returns a writer to the original stdout.
+#[cfg(unix)]
+fn redirect_stdout() -> Result<Box<dyn Write + Send>> {
+    use std::{fs::File, io, os::unix::io::FromRawFd};
+
+    io::stdout().flush()?;
+
+    // SAFETY: the duplicated descriptor is owned by the returned file
+    unsafe {
+        let stdout = libc::dup(libc::STDOUT_FILENO);
+
+        if stdout < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(anyhow!(
+                "Failed to redirect stdout: {}",
+                io::Error::last_os_error()
+            ));
+        }
+
+        Ok(Box::new(File::from_raw_fd(stdout)))
+    }
+}
+
+/// Progress output cannot be redirected on this platform, so it would be mixed
+/// with the JSON output.
+#[cfg(not(unix))]
+fn redirect_stdout() -> Result<Box<dyn Write + Send>> {
+    Err(anyhow!(
+        "JSON output (--output json) is only supported on unix platforms"
+    ))
+}
+
+#[test]
+fn test_command_output() {
+    set_output("candyMachine", "CndyV3");
+    push_output("signatures", "sig1");
+    push_output("signatures", "sig2");
+    merge_output(&json!({ "items": 10 })).unwrap();
+
+    let result = RESULT.lock().unwrap().clone();
+
+    assert_eq!(result["candyMachine"], "CndyV3");
+    assert_eq!(result["signatures"], json!(["sig1", "sig2"]));
+    assert_eq!(result["items"], 10);
+    assert!(merge_output(&10).is_err());
+}
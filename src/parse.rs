@@ -47,12 +47,12 @@ pub fn path_to_string(path: &Path) -> Result<String> {
     }
 }
 
-pub fn parse_sugar_errors(msg: &str) -> String {
-    lazy_static! {
-        static ref RE: Regex =
-            Regex::new(r"(0x[A-Za-z0-9]+)").expect("Failed to compile parse_client_error regex.");
-    }
+lazy_static! {
+    static ref RE: Regex =
+        Regex::new(r"(0x[A-Za-z0-9]+)").expect("Failed to compile parse_client_error regex.");
+}
 
+pub fn parse_sugar_errors(msg: &str) -> String {
     let mat = RE.find(msg);
 
     // If there's an RPC error code match in the message, try to parse it, otherwise return the message back.
@@ -65,6 +65,14 @@ pub fn parse_sugar_errors(msg: &str) -> String {
     }
 }
 
+/// Returns the (RPC) error code in the message, if any.
+pub fn parse_sugar_error_code(msg: &str) -> Option<u32> {
+    RE.find(msg).and_then(|m| {
+        let code = m.as_str().to_lowercase();
+        u32::from_str_radix(code.trim_start_matches("0x"), 16).ok()
+    })
+}
+
 fn find_external_program_error(code: String) -> String {
     let code = code.to_uppercase();
 
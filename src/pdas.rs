@@ -1,21 +1,32 @@
 use std::ops::Deref;
 
 use anchor_client::{
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer},
     Program,
 };
+use anchor_lang::AccountDeserialize;
 use anyhow::{anyhow, Result};
 use borsh::BorshDeserialize;
+use mpl_candy_guard::guards::{FreezeEscrow, MintCounter};
 use mpl_token_metadata::{
     pda::{find_master_edition_account, find_metadata_account},
     state::{Key, MasterEditionV2, Metadata, MAX_MASTER_EDITION_LEN},
     utils::try_from_slice_checked,
 };
+use solana_client::rpc_client::RpcClient;
 
 use crate::candy_machine::CANDY_MACHINE_ID;
 
 pub type PdaInfo<T> = (Pubkey, T);
 
+/// Allocation tracker account of the allocation guard. This mirrors
+/// `guards::allocation::AllocationTracker` of mpl-candy-guard, which is not
+/// exported by the crate (its module is private).
+#[derive(BorshDeserialize)]
+struct AllocationTracker {
+    count: u32,
+}
+
 pub struct CollectionPDA {
     pub mint: Pubkey,
     pub candy_machine: Pubkey,
@@ -96,3 +107,128 @@ pub fn find_collection_pda(candy_machine_id: &Pubkey) -> (Pubkey, u8) {
 
     Pubkey::find_program_address(collection_seeds, &CANDY_MACHINE_ID)
 }
+
+pub fn find_allocation_tracker_pda(
+    allocation_id: u8,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> (Pubkey, u8) {
+    // Derive allocation tracker PDA address
+    let allocation_seeds = &[
+        "allocation".as_bytes(),
+        &[allocation_id],
+        candy_guard_id.as_ref(),
+        candy_machine_id.as_ref(),
+    ];
+
+    Pubkey::find_program_address(allocation_seeds, &mpl_candy_guard::ID)
+}
+
+/// Returns the number of mints recorded by an allocation tracker, or `None`
+/// if the tracker has not been initialized yet.
+pub fn get_allocation_count(
+    client: &RpcClient,
+    allocation_id: u8,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> Result<Option<u32>> {
+    let (pda, _) = find_allocation_tracker_pda(allocation_id, candy_guard_id, candy_machine_id);
+    let account = client
+        .get_account_with_commitment(&pda, CommitmentConfig::confirmed())?
+        .value;
+
+    match account {
+        Some(account) => {
+            let tracker = AllocationTracker::try_from_slice(&account.data).map_err(|_| {
+                anyhow!(
+                    "Failed to deserialize allocation tracker: {}",
+                    pda.to_string()
+                )
+            })?;
+            Ok(Some(tracker.count))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn find_mint_counter_pda(
+    mint_limit_id: u8,
+    user: &Pubkey,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> (Pubkey, u8) {
+    // Derive mint counter PDA address
+    let counter_seeds = &[
+        MintCounter::PREFIX_SEED,
+        &[mint_limit_id],
+        user.as_ref(),
+        candy_guard_id.as_ref(),
+        candy_machine_id.as_ref(),
+    ];
+
+    Pubkey::find_program_address(counter_seeds, &mpl_candy_guard::ID)
+}
+
+/// Returns the number of mints recorded by a mint counter, or `None` if the
+/// counter does not exist yet (no mints for the user).
+pub fn get_mint_counter(
+    client: &RpcClient,
+    mint_limit_id: u8,
+    user: &Pubkey,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> Result<Option<u16>> {
+    let (pda, _) = find_mint_counter_pda(mint_limit_id, user, candy_guard_id, candy_machine_id);
+    let account = client
+        .get_account_with_commitment(&pda, CommitmentConfig::confirmed())?
+        .value;
+
+    match account {
+        Some(account) => {
+            let counter = MintCounter::try_from_slice(&account.data)
+                .map_err(|_| anyhow!("Failed to deserialize mint counter: {}", pda.to_string()))?;
+            Ok(Some(counter.count))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn find_allow_list_proof_pda(
+    merkle_root: &[u8; 32],
+    user: &Pubkey,
+    candy_guard_id: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> (Pubkey, u8) {
+    // Derive allow list proof PDA address
+    let proof_seeds = &[
+        "allow_list".as_bytes(),
+        merkle_root.as_ref(),
+        user.as_ref(),
+        candy_guard_id.as_ref(),
+        candy_machine_id.as_ref(),
+    ];
+
+    Pubkey::find_program_address(proof_seeds, &mpl_candy_guard::ID)
+}
+
+/// Returns the number of NFTs frozen by a freeze escrow, or `None` if the
+/// escrow has not been initialized.
+pub fn get_frozen_count(client: &RpcClient, freeze_escrow: &Pubkey) -> Result<Option<u64>> {
+    let account = client
+        .get_account_with_commitment(freeze_escrow, CommitmentConfig::confirmed())?
+        .value;
+
+    match account {
+        Some(account) => {
+            let escrow =
+                FreezeEscrow::try_deserialize(&mut account.data.as_slice()).map_err(|_| {
+                    anyhow!(
+                        "Failed to deserialize freeze escrow: {}",
+                        freeze_escrow.to_string()
+                    )
+                })?;
+            Ok(Some(escrow.frozen_count))
+        }
+        None => Ok(None),
+    }
+}
@@ -21,8 +21,10 @@ use crate::{
     candy_machine::CANDY_MACHINE_ID,
     common::*,
     config::{get_config_data, Cluster},
-    pdas::{find_candy_machine_creator_pda, find_metadata_pda},
+    mints::{get_candy_machine_mints, MintDiscovery},
+    pdas::find_metadata_pda,
     setup::get_rpc_url,
+    tx::send_transaction,
     utils::*,
 };
 
@@ -33,6 +35,8 @@ pub struct RevealArgs {
     pub config: String,
     pub timeout: Option<u64>,
     pub new_update_authority: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub discovery: MintDiscovery,
 }
 
 #[derive(Clone, Debug)]
@@ -158,25 +162,25 @@ pub async fn process_reveal(args: RevealArgs) -> Result<()> {
         solana_cluster
     };
 
-    let metadata_pubkeys = match solana_cluster {
-        Cluster::Mainnet | Cluster::Devnet | Cluster::Localnet => {
-            let client = RpcClient::new_with_timeout(
-                &rpc_url,
-                Duration::from_secs(if let Some(timeout) = args.timeout {
-                    timeout
-                } else {
-                    DEFAULT_TIMEOUT
-                }),
-            );
-            let (creator, _) = find_candy_machine_creator_pda(&candy_machine_id);
-            get_cm_creator_metadata_accounts(&client, &creator.to_string(), 0)?
-        }
-        _ => {
-            return Err(anyhow!(
-                "Cluster being used is unsupported for this command."
-            ))
-        }
-    };
+    let client = Arc::new(RpcClient::new_with_timeout(
+        &rpc_url,
+        Duration::from_secs(if let Some(timeout) = args.timeout {
+            timeout
+        } else {
+            DEFAULT_TIMEOUT
+        }),
+    ));
+    let metadata_pubkeys = get_candy_machine_mints(
+        client,
+        &candy_machine_id,
+        &solana_cluster,
+        args.discovery,
+        &args.cache,
+    )
+    .await?
+    .iter()
+    .map(find_metadata_pda)
+    .collect::<Vec<Pubkey>>();
 
     if metadata_pubkeys.is_empty() {
         spinner.finish_with_message(format!(
@@ -322,7 +326,9 @@ pub async fn process_reveal(args: RevealArgs) -> Result<()> {
     spinner.finish_and_clear();
 
     let keypair = Arc::new(sugar_config.keypair);
-    let sem = Arc::new(Semaphore::new(1000));
+    let nonce = args.nonce;
+    // each transaction advances the durable nonce, so they are sent one at a time
+    let sem = Arc::new(Semaphore::new(if nonce.is_some() { 1 } else { 1000 }));
     let reveal_results = Arc::new(Mutex::new(Vec::new()));
     let mut tx_tasks = Vec::new();
 
@@ -350,7 +356,7 @@ pub async fn process_reveal(args: RevealArgs) -> Result<()> {
                 result: RevealResult::Success,
             };
 
-            match update_metadata_value(client, keypair, item).await {
+            match update_metadata_value(client, keypair, item, nonce).await {
                 Ok(_) => {
                     let mut cache_mutex = cache.lock().unwrap();
                     let v = cache_mutex.items.get_mut(&index).unwrap();
@@ -379,6 +385,9 @@ pub async fn process_reveal(args: RevealArgs) -> Result<()> {
         .filter(|r| matches!(r.result, RevealResult::Failure(_)))
         .collect();
 
+    set_output("revealed", results.len() - errors.len());
+    set_output("failed", errors.len());
+
     if !errors.is_empty() {
         println!(
             "{WARNING_EMOJI}Some reveals failed. See the reveal cache file for details. Re-run the command."
@@ -402,9 +411,10 @@ async fn async_get_multiple_accounts(
 
 async fn update_metadata_value(
     client: Arc<RpcClient>,
-    update_authority: Arc<Keypair>,
+    update_authority: Arc<SugarSigner>,
     value: MetadataUpdateValues,
-) -> Result<(), ClientError> {
+    nonce: Option<Pubkey>,
+) -> Result<()> {
     let mut data = value.metadata.data;
     if data.uri.trim_matches(char::from(0)) != value.new_uri.trim_matches(char::from(0)) {
         data.uri = value.new_uri;
@@ -430,15 +440,7 @@ async fn update_metadata_value(
             None,
         );
 
-        let recent_blockhash = client.get_latest_blockhash()?;
-        let tx = Transaction::new_signed_with_payer(
-            &[ix],
-            Some(&update_authority.pubkey()),
-            &[&*update_authority],
-            recent_blockhash,
-        );
-
-        client.send_and_confirm_transaction(&tx)?;
+        send_transaction(&client, &[ix], &*update_authority, nonce.as_ref())?;
     }
 
     Ok(())
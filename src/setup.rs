@@ -1,27 +1,25 @@
 use std::rc::Rc;
 
-use anchor_client::{
-    solana_sdk::{
-        commitment_config::CommitmentConfig,
-        signature::{keypair::Keypair, read_keypair_file},
-    },
-    Client, Cluster,
-};
-use anyhow::{anyhow, Result};
+use anchor_client::{solana_sdk::commitment_config::CommitmentConfig, Client, Cluster};
+use anyhow::Result;
 use console::style;
 use tracing::error;
 
-use crate::{config::data::SugarConfig, constants::DEFAULT_KEYPATH, parse::*};
+use crate::{
+    config::data::SugarConfig,
+    constants::DEFAULT_KEYPATH,
+    parse::*,
+    signer::{read_signer, SugarSigner},
+};
 
-pub type SugarClient = Client<Rc<Keypair>>;
+pub type SugarClient = Client<Rc<SugarSigner>>;
 
 pub fn setup_client(sugar_config: &SugarConfig) -> Result<SugarClient> {
     let rpc_url = sugar_config.rpc_url.clone();
     let ws_url = rpc_url.replace("http", "ws");
     let cluster = Cluster::Custom(rpc_url, ws_url);
 
-    let key_bytes = sugar_config.keypair.to_bytes();
-    let signer = Rc::new(Keypair::from_bytes(&key_bytes)?);
+    let signer = Rc::new(sugar_config.keypair.clone());
 
     let opts = CommitmentConfig::confirmed();
     Ok(Client::new_with_options(cluster, signer, opts))
@@ -35,48 +33,22 @@ pub fn sugar_setup(
 
     let rpc_url = get_rpc_url(rpc_url_opt);
 
-    let keypair = match keypair_opt {
-        Some(keypair_path) => match read_keypair_file(&keypair_path) {
-            Ok(keypair) => keypair,
-            Err(e) => {
-                error!("Failed to read keypair file: {}", e);
-                return Err(anyhow!(
-                    "Failed to read keypair file: {}, {}",
-                    keypair_path,
-                    e
-                ));
-            }
-        },
-
+    let source = match keypair_opt {
+        Some(keypair_path) => keypair_path,
         None => match sol_config_option {
-            Some(ref sol_config) => match read_keypair_file(&sol_config.keypair_path) {
-                Ok(keypair) => keypair,
-                Err(e) => {
-                    error!(
-                        "Failed to read keypair file: {}, {}",
-                        &sol_config.keypair_path, e
-                    );
-                    return Err(anyhow!(
-                        "Failed to read keypair file: {}, {}",
-                        &sol_config.keypair_path,
-                        e
-                    ));
-                }
-            },
-            None => match read_keypair_file(&*shellexpand::tilde(DEFAULT_KEYPATH)) {
-                Ok(keypair) => keypair,
-                Err(e) => {
-                    error!("Failed to read keypair file: {}, {}", DEFAULT_KEYPATH, e);
-                    return Err(anyhow!(
-                        "Failed to read keypair file: {}, {}",
-                        DEFAULT_KEYPATH,
-                        e
-                    ));
-                }
-            },
+            Some(ref sol_config) => sol_config.keypair_path.clone(),
+            None => shellexpand::tilde(DEFAULT_KEYPATH).to_string(),
         },
     };
 
+    let keypair = match read_signer(&source) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            error!("{}", e);
+            return Err(e);
+        }
+    };
+
     Ok(SugarConfig { rpc_url, keypair })
 }
 
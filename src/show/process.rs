@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{ops::Deref, str::FromStr};
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anyhow::Result;
@@ -8,12 +8,19 @@ use mpl_candy_machine_core::{
     AccountVersion,
 };
 use mpl_token_metadata::state::TokenStandard;
+use serde::Serialize;
 use tabled::{
     builder::Builder,
     settings::{object::Segment, Alignment, Modify, Style},
 };
 
-use crate::{cache::load_cache, candy_machine::*, common::*, utils::*};
+use crate::{
+    cache::load_cache,
+    candy_machine::*,
+    common::*,
+    output::{merge_output, OutputFormat},
+    utils::*,
+};
 
 pub struct ShowArgs {
     pub keypair: Option<String>,
@@ -21,21 +28,74 @@ pub struct ShowArgs {
     pub cache: String,
     pub candy_machine: Option<String>,
     pub unminted: bool,
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandyMachineOutput {
+    pub candy_machine: String,
+    pub authority: String,
+    pub mint_authority: String,
+    pub collection_mint: String,
+    pub account_version: String,
+    pub token_standard: String,
+    pub rule_set: Option<String>,
+    pub features: [u8; 6],
+    pub max_supply: u64,
+    pub items_redeemed: u64,
+    pub items_available: u64,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    pub is_mutable: bool,
+    pub creators: Vec<CreatorOutput>,
+    pub hidden_settings: Option<HiddenSettingsOutput>,
+    pub config_line_settings: Option<ConfigLineSettingsOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unminted: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorOutput {
+    pub address: String,
+    pub percentage_share: u8,
+    pub verified: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiddenSettingsOutput {
+    pub name: String,
+    pub uri: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigLineSettingsOutput {
+    pub prefix_name: String,
+    pub name_length: u32,
+    pub prefix_uri: String,
+    pub uri_length: u32,
+    pub is_sequential: bool,
 }
 
 // number of indices per line
 const PER_LINE: usize = 10;
 
 pub fn process_show(args: ShowArgs) -> Result<()> {
-    println!(
-        "{} {}Looking up candy machine",
-        if args.unminted {
-            style("[1/2]").bold().dim()
-        } else {
-            style("[1/1]").bold().dim()
-        },
-        LOOKING_GLASS_EMOJI
-    );
+    if !args.output.is_json() {
+        println!(
+            "{} {}Looking up candy machine",
+            if args.unminted {
+                style("[1/2]").bold().dim()
+            } else {
+                style("[1/1]").bold().dim()
+            },
+            LOOKING_GLASS_EMOJI
+        );
+    }
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
@@ -63,7 +123,29 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     };
 
     let (cndy_state, rule_set) = load_candy_machine(&sugar_config, &candy_machine_id)?;
-    let cndy_data = cndy_state.data;
+
+    if args.output.is_json() {
+        let unminted = if args.unminted {
+            Some(get_unminted_indices(
+                &program,
+                &candy_machine_id,
+                &cndy_state,
+            )?)
+        } else {
+            None
+        };
+
+        pb.finish_and_clear();
+
+        return merge_output(&candy_machine_output(
+            &candy_machine_id,
+            &cndy_state,
+            rule_set,
+            unminted,
+        )?);
+    }
+
+    let cndy_data = &cndy_state.data;
 
     pb.finish_and_clear();
 
@@ -201,30 +283,12 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
             LOOKING_GLASS_EMOJI
         );
 
-        let start = HIDDEN_SECTION
-            + 4
-            + (cndy_data.items_available as usize) * cndy_data.get_config_line_size()
-            + (cndy_data
-                .items_available
-                .checked_div(8)
-                .expect("Numerical overflow error")
-                + 1) as usize;
-
         let pb = spinner_with_style();
         pb.set_message("Connecting...");
-        // retrieve the (raw) candy machine data
-        let data = program.rpc().get_account_data(&candy_machine_id)?;
+
+        let indices = get_unminted_indices(&program, &candy_machine_id, &cndy_state)?;
 
         pb.finish_and_clear();
-        let mut indices = vec![];
-
-        let remaining = cndy_data.items_available - cndy_state.items_redeemed;
-        for i in 0..remaining {
-            let slice = start + (i * 4) as usize;
-            indices.push(u32::from_le_bytes(
-                data[slice..slice + 4].try_into().unwrap(),
-            ));
-        }
 
         if indices.is_empty() {
             println!(
@@ -233,8 +297,6 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
                 style("All items of the candy machine have been minted.").dim()
             );
         } else {
-            // makes sure all items are in order
-            indices.sort_unstable();
             // logs all indices
             info!("unminted list: {:?}", indices);
 
@@ -265,9 +327,153 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     Ok(())
 }
 
+/// Returns the (sorted) indices of the items not minted yet.
+fn get_unminted_indices<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    candy_machine_id: &Pubkey,
+    candy_machine: &CandyMachine,
+) -> Result<Vec<u32>> {
+    let cndy_data = &candy_machine.data;
+    let start = HIDDEN_SECTION
+        + 4
+        + (cndy_data.items_available as usize) * cndy_data.get_config_line_size()
+        + (cndy_data
+            .items_available
+            .checked_div(8)
+            .expect("Numerical overflow error")
+            + 1) as usize;
+
+    // retrieve the (raw) candy machine data
+    let data = program.rpc().get_account_data(candy_machine_id)?;
+    let mut indices = vec![];
+
+    let remaining = cndy_data.items_available - candy_machine.items_redeemed;
+    for i in 0..remaining {
+        let slice = start + (i * 4) as usize;
+        indices.push(u32::from_le_bytes(
+            data[slice..slice + 4].try_into().unwrap(),
+        ));
+    }
+
+    // makes sure all items are in order
+    indices.sort_unstable();
+
+    Ok(indices)
+}
+
+pub fn candy_machine_output(
+    candy_machine_id: &Pubkey,
+    candy_machine: &CandyMachine,
+    rule_set: Option<Pubkey>,
+    unminted: Option<Vec<u32>>,
+) -> Result<CandyMachineOutput> {
+    let data = &candy_machine.data;
+    let is_v1 = matches!(candy_machine.version, AccountVersion::V1);
+
+    let hidden_settings = match &data.hidden_settings {
+        Some(hidden_settings) => Some(HiddenSettingsOutput {
+            name: hidden_settings.name.clone(),
+            uri: hidden_settings.uri.clone(),
+            hash: String::from_utf8(hidden_settings.hash.to_vec())?,
+        }),
+        None => None,
+    };
+
+    Ok(CandyMachineOutput {
+        candy_machine: candy_machine_id.to_string(),
+        authority: candy_machine.authority.to_string(),
+        mint_authority: candy_machine.mint_authority.to_string(),
+        collection_mint: candy_machine.collection_mint.to_string(),
+        account_version: if is_v1 { "V1" } else { "V2" }.to_string(),
+        token_standard:
+            if is_v1 || candy_machine.token_standard == TokenStandard::NonFungible as u8 {
+                "NonFungible"
+            } else {
+                "ProgrammableNonFungible"
+            }
+            .to_string(),
+        rule_set: if is_v1 {
+            None
+        } else {
+            rule_set.map(|rule_set| rule_set.to_string())
+        },
+        features: candy_machine.features,
+        max_supply: data.max_supply,
+        items_redeemed: candy_machine.items_redeemed,
+        items_available: data.items_available,
+        symbol: data.symbol.trim_end_matches(NULL_STRING).to_string(),
+        seller_fee_basis_points: data.seller_fee_basis_points,
+        is_mutable: data.is_mutable,
+        creators: data
+            .creators
+            .iter()
+            .map(|creator| CreatorOutput {
+                address: creator.address.to_string(),
+                percentage_share: creator.percentage_share,
+                verified: creator.verified,
+            })
+            .collect(),
+        hidden_settings,
+        config_line_settings: data.config_line_settings.as_ref().map(|settings| {
+            ConfigLineSettingsOutput {
+                prefix_name: settings.prefix_name.clone(),
+                name_length: settings.name_length,
+                prefix_uri: settings.prefix_uri.clone(),
+                uri_length: settings.uri_length,
+                is_sequential: settings.is_sequential,
+            }
+        }),
+        unminted,
+    })
+}
+
 pub fn print_with_style<S>(indent: &str, key: &str, value: S)
 where
     S: core::fmt::Display,
 {
     println!(" {} {}", style(format!("{indent}:.. {key}:")).dim(), value);
 }
+
+#[test]
+fn test_candy_machine_output() {
+    use mpl_candy_machine_core::{CandyMachineData, ConfigLineSettings, Creator};
+
+    let creator = Pubkey::new_unique();
+    let candy_machine = CandyMachine {
+        items_redeemed: 2,
+        data: CandyMachineData {
+            items_available: 10,
+            symbol: format!("SUGAR{}", NULL_STRING.repeat(5)),
+            seller_fee_basis_points: 500,
+            creators: vec![Creator {
+                address: creator,
+                verified: true,
+                percentage_share: 100,
+            }],
+            config_line_settings: Some(ConfigLineSettings {
+                prefix_name: "Sugar #".to_string(),
+                name_length: 4,
+                prefix_uri: String::new(),
+                uri_length: 200,
+                is_sequential: false,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let candy_machine_id = Pubkey::new_unique();
+    let output = serde_json::to_value(
+        candy_machine_output(&candy_machine_id, &candy_machine, None, Some(vec![0, 3])).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(output["candyMachine"], candy_machine_id.to_string());
+    assert_eq!(output["symbol"], "SUGAR");
+    assert_eq!(output["itemsRedeemed"], 2);
+    assert_eq!(output["creators"][0]["address"], creator.to_string());
+    assert_eq!(output["creators"][0]["percentageShare"], 100);
+    assert!(output["hiddenSettings"].is_null());
+    assert_eq!(output["configLineSettings"]["prefixName"], "Sugar #");
+    assert_eq!(output["unminted"], json!([0, 3]));
+}
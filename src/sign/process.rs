@@ -24,7 +24,8 @@ use crate::{
     candy_machine::CANDY_MACHINE_ID,
     common::*,
     config::{Cluster, SugarConfig},
-    pdas::{find_candy_machine_creator_pda, find_metadata_pda},
+    mints::{get_candy_machine_mints, MintDiscovery},
+    pdas::find_metadata_pda,
     setup::{get_rpc_url, setup_client, sugar_setup},
     utils::*,
 };
@@ -35,6 +36,7 @@ pub struct SignArgs {
     pub cache: String,
     pub rpc_url: Option<String>,
     pub mint: Option<String>,
+    pub discovery: MintDiscovery,
 }
 
 pub async fn process_sign(args: SignArgs) -> Result<()> {
@@ -109,19 +111,21 @@ pub async fn process_sign(args: SignArgs) -> Result<()> {
             solana_cluster
         };
 
-        let account_keys = match solana_cluster {
-            Cluster::Devnet | Cluster::Localnet | Cluster::Mainnet => {
-                let client = RpcClient::new_with_timeout(&rpc_url, Duration::from_secs(300));
-                let (creator, _) = find_candy_machine_creator_pda(&candy_machine_id);
-                let creator = bs58::encode(creator).into_string();
-                get_cm_creator_metadata_accounts(&client, &creator, 0)?
-            }
-            _ => {
-                return Err(anyhow!(
-                    "Cluster being used is unsupported for this command."
-                ))
-            }
-        };
+        let client = Arc::new(RpcClient::new_with_timeout(
+            &rpc_url,
+            Duration::from_secs(300),
+        ));
+        let account_keys = get_candy_machine_mints(
+            client,
+            &candy_machine_id,
+            &solana_cluster,
+            args.discovery,
+            &args.cache,
+        )
+        .await?
+        .iter()
+        .map(find_metadata_pda)
+        .collect::<Vec<Pubkey>>();
 
         if account_keys.is_empty() {
             pb.finish_with_message(format!("{}", style("No NFTs found.").green().bold()));
@@ -188,7 +192,12 @@ async fn sign(config: Arc<SugarConfig>, metadata: Pubkey) -> Result<(), Error> {
     // Send tx with retries.
     retry(
         Exponential::from_millis_with_factor(250, 2.0).take(3),
-        || program.rpc().send_and_confirm_transaction(&tx),
+        || {
+            program
+                .rpc()
+                .send_and_confirm_transaction(&tx)
+                .map_err(Box::new)
+        },
     )?;
 
     Ok(())
@@ -0,0 +1,279 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use anchor_client::solana_sdk::{
+    derivation_path::DerivationPath,
+    pubkey::Pubkey,
+    signature::{
+        generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed,
+        keypair_from_seed_and_derivation_path, read_keypair_file, Keypair, Signature, Signer,
+    },
+    signer::SignerError,
+};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use dialoguer::Password;
+use lazy_static::lazy_static;
+
+use crate::utils::get_dialoguer_theme;
+
+/// Prefix of a signer read from a base58 secret key in an environment variable.
+pub const ENV_SIGNER_PREFIX: &str = "env:";
+
+/// Prefix of a signer derived from a seed phrase prompt, optionally followed by
+/// a derivation path (e.g., `prompt:0/0` or `prompt:m/44'/501'/0'/0'`).
+pub const PROMPT_SIGNER_PREFIX: &str = "prompt:";
+
+/// Prefix of an external signing command. The command is run with the argument
+/// `pubkey` to print its public key, and with `sign` to sign the message bytes
+/// read from stdin, printing the base58 signature to stdout.
+pub const COMMAND_SIGNER_PREFIX: &str = "command:";
+
+lazy_static! {
+    // signers already resolved, so a seed phrase is only prompted once
+    static ref SIGNERS: Mutex<HashMap<String, SugarSigner>> = Mutex::new(HashMap::new());
+}
+
+/// Signer of sugar transactions.
+#[derive(Debug)]
+pub enum SugarSigner {
+    Keypair(Keypair),
+    Command { pubkey: Pubkey, command: String },
+}
+
+impl Clone for SugarSigner {
+    fn clone(&self) -> Self {
+        match self {
+            SugarSigner::Keypair(keypair) => SugarSigner::Keypair(
+                Keypair::from_bytes(&keypair.to_bytes()).expect("Invalid keypair bytes"),
+            ),
+            SugarSigner::Command { pubkey, command } => SugarSigner::Command {
+                pubkey: *pubkey,
+                command: command.clone(),
+            },
+        }
+    }
+}
+
+impl From<Keypair> for SugarSigner {
+    fn from(keypair: Keypair) -> Self {
+        SugarSigner::Keypair(keypair)
+    }
+}
+
+impl Signer for SugarSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        match self {
+            SugarSigner::Keypair(keypair) => keypair.try_pubkey(),
+            SugarSigner::Command { pubkey, .. } => Ok(*pubkey),
+        }
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        match self {
+            SugarSigner::Keypair(keypair) => keypair.try_sign_message(message),
+            SugarSigner::Command { pubkey, command } => {
+                let output = run_signer_command(command, "sign", Some(message))
+                    .map_err(|err| SignerError::Custom(err.to_string()))?;
+                let signature = Signature::from_str(&output).map_err(|_| {
+                    SignerError::Custom(format!("Invalid signature from signer command: {output}"))
+                })?;
+
+                if !signature.verify(pubkey.as_ref(), message) {
+                    return Err(SignerError::Custom(
+                        "Signer command returned an invalid signature".to_string(),
+                    ));
+                }
+
+                Ok(signature)
+            }
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+/// Signer for Bundlr uploads.
+pub struct BundlrSigner(pub SugarSigner);
+
+impl bundlr_sdk::Signer for BundlrSigner {
+    // same as the Solana (ed25519) signer
+    const SIG_TYPE: u16 = 2;
+    const SIG_LENGTH: u16 = 64;
+    const PUB_LENGTH: u16 = 32;
+
+    fn sign(&self, message: Bytes) -> Result<Bytes, bundlr_sdk::error::BundlrError> {
+        let signature = self
+            .0
+            .try_sign_message(&message)
+            .map_err(|_| bundlr_sdk::error::BundlrError::InvalidSignature)?;
+
+        Ok(Bytes::copy_from_slice(signature.as_ref()))
+    }
+
+    fn pub_key(&self) -> Bytes {
+        Bytes::copy_from_slice(self.0.pubkey().as_ref())
+    }
+}
+
+/// Reads a signer from a keypair file, an environment variable, a seed phrase
+/// prompt or an external signing command.
+pub fn read_signer(source: &str) -> Result<SugarSigner> {
+    if let Some(signer) = SIGNERS.lock().unwrap().get(source) {
+        return Ok(signer.clone());
+    }
+
+    let signer = if let Some(variable) = source.strip_prefix(ENV_SIGNER_PREFIX) {
+        let secret = env::var(variable)
+            .map_err(|_| anyhow!("Environment variable {} is not set", variable))?;
+        let bytes = bs58::decode(secret.trim())
+            .into_vec()
+            .map_err(|_| anyhow!("Environment variable {} is not a base58 secret", variable))?;
+        SugarSigner::Keypair(Keypair::from_bytes(&bytes).map_err(|_| {
+            anyhow!(
+                "Environment variable {} is not a valid secret key",
+                variable
+            )
+        })?)
+    } else if let Some(path) = source.strip_prefix(PROMPT_SIGNER_PREFIX) {
+        let derivation_path = if path.is_empty() {
+            None
+        } else {
+            Some(parse_derivation_path(path)?)
+        };
+        SugarSigner::Keypair(prompt_seed_phrase(derivation_path)?)
+    } else if let Some(command) = source.strip_prefix(COMMAND_SIGNER_PREFIX) {
+        let output = run_signer_command(command, "pubkey", None)?;
+        let pubkey = Pubkey::from_str(&output)
+            .map_err(|_| anyhow!("Invalid public key from signer command: {}", output))?;
+        SugarSigner::Command {
+            pubkey,
+            command: command.to_string(),
+        }
+    } else {
+        SugarSigner::Keypair(
+            read_keypair_file(&*shellexpand::tilde(source))
+                .map_err(|err| anyhow!("Failed to read keypair file: {}, {}", source, err))?,
+        )
+    };
+
+    SIGNERS
+        .lock()
+        .unwrap()
+        .insert(source.to_string(), signer.clone());
+
+    Ok(signer)
+}
+
+/// Parses a derivation path, either as `<account>/<change>` or the full path.
+fn parse_derivation_path(path: &str) -> Result<DerivationPath> {
+    let key = match path.strip_prefix("m/44'/501'") {
+        Some(key) => key.trim_start_matches('/').replace('\'', ""),
+        None => path.to_string(),
+    };
+
+    DerivationPath::from_key_str(if key.is_empty() { "m" } else { &key })
+        .map_err(|err| anyhow!("Invalid derivation path {}: {}", path, err))
+}
+
+fn prompt_seed_phrase(derivation_path: Option<DerivationPath>) -> Result<Keypair> {
+    let theme = get_dialoguer_theme();
+
+    let seed_phrase = Password::with_theme(&theme)
+        .with_prompt("Seed phrase")
+        .interact()?;
+    let passphrase = Password::with_theme(&theme)
+        .with_prompt("Passphrase (empty for none)")
+        .allow_empty_password(true)
+        .interact()?;
+
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&seed_phrase, &passphrase);
+
+    let keypair = match derivation_path {
+        Some(derivation_path) => {
+            keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        }
+        None => keypair_from_seed(&seed),
+    }
+    .map_err(|err| anyhow!("Failed to derive keypair from seed phrase: {}", err))?;
+
+    Ok(keypair)
+}
+
+/// Runs the external signing command with the given argument, writing the input
+/// (if any) to its stdin, and returns its (trimmed) output.
+fn run_signer_command(command: &str, argument: &str, input: Option<&[u8]>) -> Result<String> {
+    let command_line = format!("{command} {argument}");
+
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/C", &command_line]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", &command_line]);
+        process
+    };
+
+    let mut child = process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| anyhow!("Failed to run signer command '{}': {}", command, err))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.unwrap_or_default())?;
+    }
+
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Signer command '{}' failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[test]
+fn test_read_env_signer() {
+    let keypair = Keypair::new();
+    let variable = format!("SUGAR_TEST_SIGNER_{}", keypair.pubkey());
+    env::set_var(&variable, keypair.to_base58_string());
+
+    let signer = read_signer(&format!("{ENV_SIGNER_PREFIX}{variable}")).unwrap();
+    assert_eq!(signer.pubkey(), keypair.pubkey());
+
+    let message = b"sugar";
+    assert!(signer
+        .sign_message(message)
+        .verify(keypair.pubkey().as_ref(), message));
+
+    assert!(read_signer(&format!("{ENV_SIGNER_PREFIX}SUGAR_TEST_SIGNER_MISSING")).is_err());
+}
+
+#[test]
+fn test_parse_derivation_path() {
+    let expected = DerivationPath::new_bip44(Some(1), Some(0));
+
+    assert_eq!(parse_derivation_path("1/0").unwrap(), expected);
+    assert_eq!(parse_derivation_path("m/44'/501'/1'/0'").unwrap(), expected);
+    assert_eq!(
+        parse_derivation_path("m/44'/501'").unwrap(),
+        DerivationPath::new_bip44(None, None)
+    );
+    assert!(parse_derivation_path("1/0/2").is_err());
+}
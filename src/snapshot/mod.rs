@@ -0,0 +1,3 @@
+mod process;
+
+pub use process::*;
@@ -0,0 +1,332 @@
+use std::{
+    fs,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anchor_client::solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use anyhow::Result;
+use console::style;
+use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+use spl_token::state::Account as SplAccount;
+use tokio::sync::Semaphore;
+
+use crate::{
+    cache::load_cache,
+    candy_machine::CANDY_MACHINE_ID,
+    common::*,
+    config::Cluster,
+    mints::{get_candy_machine_mints, MintDiscovery},
+    pdas::find_metadata_pda,
+    setup::get_rpc_url,
+    utils::*,
+};
+
+pub struct SnapshotArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub candy_machine: Option<String>,
+    pub file: String,
+    pub timeout: Option<u64>,
+    pub discovery: MintDiscovery,
+}
+
+/// Holder of an NFT. The fields `address` (owner) and `count` make the snapshot
+/// usable as an airdrop list.
+#[derive(Clone, Debug, Serialize)]
+pub struct Holder {
+    pub address: String,
+    pub count: u64,
+    pub mint: String,
+    pub token_account: String,
+    pub frozen: bool,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JRpcResponse {
+    value: Vec<TokenAccount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenAccount {
+    address: String,
+    amount: String,
+}
+
+// Timeout for the GPA call (in seconds).
+const DEFAULT_TIMEOUT: u64 = 300;
+
+/// Maximum number of accounts per `getMultipleAccounts` request.
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+pub async fn process_snapshot(args: SnapshotArgs) -> Result<()> {
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url.clone())?;
+    let client = setup_client(&sugar_config)?;
+    let program = client.program(CANDY_MACHINE_ID);
+
+    // the candy machine id specified takes precedence over the one from the cache
+
+    let candy_machine_id = match args.candy_machine {
+        Some(candy_machine_id) => candy_machine_id,
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.candy_machine
+        }
+    };
+
+    let candy_pubkey = Pubkey::from_str(&candy_machine_id)
+        .map_err(|_| anyhow!("Failed to parse candy machine id: {}", candy_machine_id))?;
+
+    println!(
+        "{} {}Getting minted NFTs for candy machine {}",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        candy_machine_id
+    );
+
+    let spinner = spinner_with_style();
+    spinner.set_message("Loading...");
+
+    let solana_cluster: Cluster = get_cluster(program.rpc())?;
+    let rpc_url = get_rpc_url(args.rpc_url);
+
+    let solana_cluster = if rpc_url.ends_with("8899") {
+        Cluster::Localnet
+    } else {
+        solana_cluster
+    };
+
+    let client = Arc::new(RpcClient::new_with_timeout(
+        &rpc_url,
+        Duration::from_secs(args.timeout.unwrap_or(DEFAULT_TIMEOUT)),
+    ));
+
+    let mints = get_candy_machine_mints(
+        client.clone(),
+        &candy_pubkey,
+        &solana_cluster,
+        args.discovery,
+        &args.cache,
+    )
+    .await?;
+
+    if mints.is_empty() {
+        spinner.finish_with_message(format!("{}", style("No NFTs found.").red().bold()));
+        return Err(anyhow!(
+            "No minted NFTs found for candy machine {}",
+            candy_machine_id
+        ));
+    }
+
+    spinner.finish_with_message(format!("Found {} NFTs", mints.len()));
+
+    println!(
+        "\n{} {}Resolving current owners",
+        style("[2/3]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let holders = get_holders(client, mints.clone()).await?;
+
+    println!(
+        "\n{} {}Writing snapshot",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    write_snapshot(&holders, &args.file)?;
+
+    println!(
+        "{} {} of {} NFTs",
+        style("Holders:").bold(),
+        holders.len(),
+        mints.len()
+    );
+    println!("{} {}", style("Snapshot:").bold(), args.file);
+    set_output("snapshot", &args.file);
+
+    if holders.len() < mints.len() {
+        println!(
+            "\n{}{} {} NFT(s) without a current holder (burned or failed to load)",
+            WARNING_EMOJI,
+            style("warning:").yellow().bold(),
+            mints.len() - holders.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Finds the current holder of each mint, using its largest token account.
+async fn get_holders(client: Arc<RpcClient>, mints: Vec<Pubkey>) -> Result<Vec<Holder>> {
+    let pb = progress_bar_with_style(mints.len() as u64);
+    let semaphore = Arc::new(Semaphore::new(100));
+    let token_accounts = Arc::new(Mutex::new(Vec::new()));
+    let mut tasks = Vec::new();
+
+    for mint in mints {
+        let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+        let client = client.clone();
+        let token_accounts = token_accounts.clone();
+        let pb = pb.clone();
+
+        // the requests are sent with the blocking client
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+
+            let request = RpcRequest::Custom {
+                method: "getTokenLargestAccounts",
+            };
+            let params = json!([mint.to_string(), { "commitment": "confirmed" }]);
+
+            match client.send::<JRpcResponse>(request, params) {
+                Ok(response) => {
+                    // burned NFTs have no token account holding the token
+                    if let Some(account) = response
+                        .value
+                        .into_iter()
+                        .find(|account| account.amount != "0")
+                    {
+                        if let Ok(address) = Pubkey::from_str(&account.address) {
+                            token_accounts.lock().unwrap().push((mint, address));
+                        }
+                    }
+                }
+                Err(err) => error!("Failed to get token accounts of {}: {}", mint, err),
+            }
+
+            pb.inc(1);
+        }));
+    }
+
+    for task in tasks {
+        task.await?;
+    }
+
+    pb.finish_and_clear();
+
+    let token_accounts = token_accounts.lock().unwrap().clone();
+    let mut holders = Vec::with_capacity(token_accounts.len());
+
+    for chunk in token_accounts.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let accounts = client.get_multiple_accounts(
+            &chunk
+                .iter()
+                .map(|(_, account)| *account)
+                .collect::<Vec<_>>(),
+        )?;
+        let metadata_accounts = client.get_multiple_accounts(
+            &chunk
+                .iter()
+                .map(|(mint, _)| find_metadata_pda(mint))
+                .collect::<Vec<_>>(),
+        )?;
+
+        for (((mint, token_account), account), metadata) in
+            chunk.iter().zip(accounts).zip(metadata_accounts)
+        {
+            let token = match account
+                .filter(|account| account.data.len() >= SplAccount::LEN)
+                .and_then(|account| SplAccount::unpack(&account.data[..SplAccount::LEN]).ok())
+            {
+                Some(token) => token,
+                None => continue,
+            };
+
+            let name = metadata
+                .and_then(|metadata| Metadata::safe_deserialize(&metadata.data).ok())
+                .map(|metadata| metadata.data.name.trim_matches(char::from(0)).to_string())
+                .unwrap_or_default();
+
+            holders.push(Holder {
+                address: token.owner.to_string(),
+                count: 1,
+                mint: mint.to_string(),
+                token_account: token_account.to_string(),
+                frozen: token.is_frozen(),
+                name,
+            });
+        }
+    }
+
+    holders.sort_by(|a, b| a.address.cmp(&b.address).then(a.mint.cmp(&b.mint)));
+
+    Ok(holders)
+}
+
+/// Writes the snapshot as CSV, JSON lines (.jsonl) or JSON (.json), depending
+/// on the file extension.
+fn write_snapshot(holders: &[Holder], path: &str) -> Result<()> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let content = match extension.as_deref() {
+        Some("json") => serde_json::to_string_pretty(holders)?,
+        Some("jsonl") | Some("ndjson") => {
+            let mut lines = Vec::with_capacity(holders.len());
+            for holder in holders {
+                lines.push(serde_json::to_string(holder)?);
+            }
+            lines.join("\n")
+        }
+        _ => holders_to_csv(holders),
+    };
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn holders_to_csv(holders: &[Holder]) -> String {
+    let mut csv = String::from("address,count,mint,token_account,frozen,name\n");
+
+    for holder in holders {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            holder.address,
+            holder.count,
+            holder.mint,
+            holder.token_account,
+            holder.frozen,
+            escape_csv_value(&holder.name)
+        ));
+    }
+
+    csv
+}
+
+#[test]
+fn test_holders_to_csv() {
+    let holder = Holder {
+        address: "11111111111111111111111111111111".to_string(),
+        count: 1,
+        mint: "SysvarRent111111111111111111111111111111111".to_string(),
+        token_account: "SysvarC1ock11111111111111111111111111111111".to_string(),
+        frozen: true,
+        name: "Sugar \"#1\", gold".to_string(),
+    };
+
+    let csv = holders_to_csv(&[holder]);
+    let mut lines = csv.lines();
+
+    assert_eq!(
+        lines.next(),
+        Some("address,count,mint,token_account,frozen,name")
+    );
+    assert_eq!(
+        lines.next(),
+        Some(
+            "11111111111111111111111111111111,1,SysvarRent111111111111111111111111111111111,\
+             SysvarC1ock11111111111111111111111111111111,true,\"Sugar \"\"#1\"\", gold\""
+        )
+    );
+}
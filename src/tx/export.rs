@@ -0,0 +1,194 @@
+use std::{
+    fmt, fs,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use anchor_client::solana_sdk::{
+    instruction::Instruction, message::Message, pubkey::Pubkey, transaction::Transaction,
+};
+use anyhow::{anyhow, Result};
+use console::style;
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+
+use crate::{
+    constants::PAPER_EMOJI,
+    output::set_output,
+    tx::{get_nonce_data, with_advance_nonce},
+};
+
+/// Options to export unsigned transactions to a file instead of sending them.
+/// The transactions are collected while the command runs and written by
+/// [`TxExport::write`].
+#[derive(Clone, Debug)]
+pub struct TxExport {
+    pub path: String,
+    /// Authority of the exported transactions (defaults to the keypair).
+    pub authority: Option<Pubkey>,
+    pub encoding: TxEncoding,
+    transactions: Arc<Mutex<Vec<ExportedTransaction>>>,
+}
+
+impl TxExport {
+    pub fn new(path: String, authority: Option<String>, encoding: TxEncoding) -> Result<Self> {
+        let authority = authority
+            .map(|authority| {
+                Pubkey::from_str(&authority)
+                    .map_err(|_| anyhow!("Failed to parse export authority: {}", authority))
+            })
+            .transpose()?;
+
+        Ok(TxExport {
+            path,
+            authority,
+            encoding,
+            transactions: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// Builds an unsigned transaction with the authority as the fee payer and
+    /// adds it to the export. When a durable nonce is used, the transaction
+    /// does not expire until the nonce is advanced. A nonce account covers a
+    /// single pending transaction, since the first one to land advances it, so
+    /// only one transaction of the export can use it.
+    pub fn add_transaction(
+        &self,
+        rpc_client: &RpcClient,
+        instructions: &[Instruction],
+        authority: &Pubkey,
+        nonce: Option<&Pubkey>,
+        description: &str,
+    ) -> Result<()> {
+        if nonce.is_some() && !self.transactions.lock().unwrap().is_empty() {
+            return Err(anyhow!(
+                "A durable nonce account covers a single pending transaction, so --nonce cannot \
+                be used to export more than one transaction"
+            ));
+        }
+
+        let message = match nonce {
+            Some(nonce) => {
+                let data = get_nonce_data(rpc_client, nonce)?;
+                Message::new_with_blockhash(
+                    &with_advance_nonce(instructions, nonce, &data.authority),
+                    Some(authority),
+                    &data.blockhash(),
+                )
+            }
+            None => Message::new_with_blockhash(
+                instructions,
+                Some(authority),
+                &rpc_client.get_latest_blockhash()?,
+            ),
+        };
+        let signers = message.account_keys[..message.header.num_required_signatures as usize]
+            .iter()
+            .map(|signer| signer.to_string())
+            .collect();
+
+        let transaction = ExportedTransaction {
+            description: description.to_string(),
+            encoding: self.encoding,
+            signers,
+            transaction: encode_transaction(&Transaction::new_unsigned(message), self.encoding)?,
+        };
+        self.transactions.lock().unwrap().push(transaction);
+
+        Ok(())
+    }
+
+    /// Writes the exported transactions to the export file.
+    pub fn write(&self) -> Result<()> {
+        let transactions = self.transactions.lock().unwrap();
+
+        if transactions.is_empty() {
+            return Err(anyhow!("No transactions to export"));
+        }
+
+        fs::write(&self.path, serde_json::to_string_pretty(&*transactions)?)?;
+
+        println!(
+            "\n{}{} {} transaction(s) to {}",
+            PAPER_EMOJI,
+            style("Exported").bold(),
+            transactions.len(),
+            self.path
+        );
+        println!("Sign them externally and broadcast them with 'sugar tx submit'.");
+
+        set_output("exportFile", &self.path);
+        set_output("exportedTransactions", transactions.len());
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TxEncoding {
+    #[default]
+    Base58,
+    Base64,
+}
+
+impl fmt::Display for TxEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TxEncoding::Base58 => write!(f, "base58"),
+            TxEncoding::Base64 => write!(f, "base64"),
+        }
+    }
+}
+
+impl FromStr for TxEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "base58" => Ok(TxEncoding::Base58),
+            "base64" => Ok(TxEncoding::Base64),
+            _ => Err(anyhow!(
+                "Invalid transaction encoding: {s} (expected 'base58' or 'base64')"
+            )),
+        }
+    }
+}
+
+/// Transaction exported to be signed externally.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportedTransaction {
+    pub description: String,
+    pub encoding: TxEncoding,
+    /// Accounts required to sign the transaction.
+    pub signers: Vec<String>,
+    pub transaction: String,
+}
+
+/// Returns the authority of an operation: the export authority when exporting
+/// transactions, otherwise the payer.
+pub fn get_tx_authority(export: &Option<TxExport>, payer: Pubkey) -> Pubkey {
+    export
+        .as_ref()
+        .and_then(|export| export.authority)
+        .unwrap_or(payer)
+}
+
+pub fn encode_transaction(transaction: &Transaction, encoding: TxEncoding) -> Result<String> {
+    let bytes = bincode::serialize(transaction)?;
+
+    Ok(match encoding {
+        TxEncoding::Base58 => bs58::encode(bytes).into_string(),
+        TxEncoding::Base64 => BASE64.encode(&bytes),
+    })
+}
+
+pub fn decode_transaction(transaction: &str, encoding: TxEncoding) -> Result<Transaction> {
+    let bytes = match encoding {
+        TxEncoding::Base58 => bs58::decode(transaction.trim()).into_vec()?,
+        TxEncoding::Base64 => BASE64.decode(transaction.trim().as_bytes())?,
+    };
+
+    Ok(bincode::deserialize(&bytes)?)
+}
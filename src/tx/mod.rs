@@ -0,0 +1,7 @@
+pub mod export;
+pub mod nonce;
+pub mod submit;
+
+pub use export::*;
+pub use nonce::*;
+pub use submit::*;
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    nonce::{state::Data, State},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use anyhow::{anyhow, Result};
+use console::style;
+use solana_client::{nonce_utils, rpc_client::RpcClient};
+
+use crate::{
+    common::{push_output, set_output, COMPUTER_EMOJI, PAPER_EMOJI},
+    setup::sugar_setup,
+    tx::TxExport,
+    utils::spinner_with_style,
+};
+
+pub struct TxCreateNonceArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub authority: Option<String>,
+}
+
+pub fn process_tx_create_nonce(args: TxCreateNonceArgs) -> Result<()> {
+    let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
+    let rpc_client =
+        RpcClient::new_with_commitment(&sugar_config.rpc_url, CommitmentConfig::confirmed());
+
+    let payer = sugar_config.keypair.pubkey();
+    let authority = match args.authority {
+        Some(authority) => Pubkey::from_str(&authority)
+            .map_err(|_| anyhow!("Failed to parse nonce authority: {}", authority))?,
+        None => payer,
+    };
+
+    println!(
+        "{} {}Creating nonce account",
+        style("[1/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let nonce_account = Keypair::new();
+    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(State::size())?;
+
+    println!(
+        "Rent for the nonce account: ◎ {}",
+        lamports as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending create nonce transaction...");
+
+    let instructions = system_instruction::create_nonce_account(
+        &payer,
+        &nonce_account.pubkey(),
+        &authority,
+        lamports,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&sugar_config.keypair as &dyn Signer, &nonce_account],
+        rpc_client.get_latest_blockhash()?,
+    );
+    let signature = rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    pb.finish_with_message(format!(
+        "{} {}",
+        style("Create nonce signature:").bold(),
+        signature
+    ));
+
+    println!(
+        "\n{} {}Nonce account",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let data = get_nonce_data(&rpc_client, &nonce_account.pubkey())?;
+
+    println!(
+        "{} {}",
+        style("Nonce account:").bold(),
+        nonce_account.pubkey()
+    );
+    println!("{} {}", style("Nonce authority:").bold(), data.authority);
+    println!("{} {}", style("Nonce:").bold(), data.blockhash());
+    println!("\nUse it with '--nonce {}'.", nonce_account.pubkey());
+
+    set_output("nonceAccount", nonce_account.pubkey().to_string());
+    set_output("nonceAuthority", data.authority.to_string());
+    set_output("nonce", data.blockhash().to_string());
+    push_output("signatures", signature.to_string());
+
+    Ok(())
+}
+
+/// Returns the data (authority and stored blockhash) of a durable nonce account.
+pub fn get_nonce_data(rpc_client: &RpcClient, nonce: &Pubkey) -> Result<Data> {
+    let account =
+        nonce_utils::get_account_with_commitment(rpc_client, nonce, rpc_client.commitment())
+            .map_err(|err| anyhow!("Failed to load nonce account {}: {}", nonce, err))?;
+
+    nonce_utils::data_from_account(&account)
+        .map_err(|err| anyhow!("Invalid nonce account {}: {}", nonce, err))
+}
+
+/// Returns the instructions with the advance nonce instruction prepended.
+pub fn with_advance_nonce(
+    instructions: &[Instruction],
+    nonce: &Pubkey,
+    authority: &Pubkey,
+) -> Vec<Instruction> {
+    let mut nonce_instructions = vec![system_instruction::advance_nonce_account(nonce, authority)];
+    nonce_instructions.extend_from_slice(instructions);
+    nonce_instructions
+}
+
+/// Signs and sends a transaction with the signer as the fee payer, using the
+/// durable nonce (if any) instead of a recent blockhash.
+pub fn send_transaction(
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    signer: &dyn Signer,
+    nonce: Option<&Pubkey>,
+) -> Result<Signature> {
+    let payer = signer.pubkey();
+
+    let (instructions, blockhash) = match nonce {
+        Some(nonce) => {
+            let data = get_nonce_data(rpc_client, nonce)?;
+
+            if data.authority != payer {
+                return Err(anyhow!(
+                    "Nonce authority {} does not match the keypair {}",
+                    data.authority,
+                    payer
+                ));
+            }
+
+            (
+                with_advance_nonce(instructions, nonce, &data.authority),
+                data.blockhash(),
+            )
+        }
+        None => (instructions.to_vec(), rpc_client.get_latest_blockhash()?),
+    };
+
+    let transaction =
+        Transaction::new_signed_with_payer(&instructions, Some(&payer), &[signer], blockhash);
+    let signature = rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    push_output("signatures", signature.to_string());
+
+    Ok(signature)
+}
+
+/// Adds the transaction to the export, when exporting transactions, otherwise
+/// signs and sends it. Returns the signature of a sent transaction.
+pub fn send_or_export(
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    signer: &dyn Signer,
+    authority: &Pubkey,
+    export_tx: &Option<TxExport>,
+    nonce: &Option<Pubkey>,
+    description: &str,
+) -> Result<Option<Signature>> {
+    if let Some(export) = export_tx {
+        export.add_transaction(
+            rpc_client,
+            instructions,
+            authority,
+            nonce.as_ref(),
+            description,
+        )?;
+        return Ok(None);
+    }
+
+    Ok(Some(send_transaction(
+        rpc_client,
+        instructions,
+        signer,
+        nonce.as_ref(),
+    )?))
+}
+
+#[test]
+fn test_with_advance_nonce() {
+    let nonce = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let instruction = system_instruction::transfer(&authority, &Pubkey::new_unique(), 1);
+
+    let instructions = with_advance_nonce(std::slice::from_ref(&instruction), &nonce, &authority);
+
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(
+        instructions[0],
+        system_instruction::advance_nonce_account(&nonce, &authority)
+    );
+    assert_eq!(instructions[1], instruction);
+}
@@ -0,0 +1,191 @@
+use std::fs;
+
+use anchor_client::solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use anyhow::{anyhow, Result};
+use console::style;
+use solana_client::rpc_client::RpcClient;
+
+use crate::{
+    common::{error, push_output, set_output, COMPUTER_EMOJI, PAPER_EMOJI},
+    parse::parse_sugar_errors,
+    setup::get_rpc_url,
+    signer::read_signer,
+    tx::{decode_transaction, ExportedTransaction, TxEncoding},
+    utils::spinner_with_style,
+};
+
+pub struct TxSubmitArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub file: String,
+}
+
+pub fn process_tx_submit(args: TxSubmitArgs) -> Result<()> {
+    println!(
+        "{} {}Loading transactions",
+        style("[1/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let transactions = load_transactions(&args.file)?;
+
+    if transactions.is_empty() {
+        return Err(anyhow!("No transactions found in {}", args.file));
+    }
+
+    println!(
+        "Found {} transaction(s) in {}",
+        transactions.len(),
+        args.file
+    );
+
+    // the keypair is optional, and only used if it is a missing signer
+    let keypair = args.keypair.as_deref().map(read_signer).transpose()?;
+
+    let rpc_client =
+        RpcClient::new_with_commitment(get_rpc_url(args.rpc_url), CommitmentConfig::confirmed());
+
+    println!(
+        "\n{} {}Submitting transactions",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+
+    let mut errors = 0;
+
+    for (description, mut transaction) in transactions {
+        if let Some(keypair) = &keypair {
+            let required = &transaction.message.account_keys
+                [..transaction.message.header.num_required_signatures as usize];
+
+            if required.contains(&keypair.pubkey()) {
+                let blockhash = transaction.message.recent_blockhash;
+                transaction.try_partial_sign(&[keypair], blockhash)?;
+            }
+        }
+
+        let pb = spinner_with_style();
+        pb.set_message(format!("Sending {description}..."));
+
+        match submit_transaction(&rpc_client, &transaction) {
+            Ok(signature) => {
+                push_output("signatures", signature.to_string());
+                pb.finish_with_message(format!(
+                    "{} {}",
+                    style(format!("{description}:")).bold(),
+                    signature
+                ))
+            }
+            Err(err) => {
+                errors += 1;
+                error!("Failed to submit {}: {}", description, err);
+                pb.abandon_with_message(format!(
+                    "{} {}",
+                    style(format!("{description} failed:")).red().bold(),
+                    parse_sugar_errors(&err.to_string())
+                ));
+            }
+        }
+    }
+
+    set_output("failed", errors);
+
+    if errors > 0 {
+        return Err(anyhow!("{} transaction(s) failed to submit", errors));
+    }
+
+    Ok(())
+}
+
+/// Loads the transactions from either a file exported by sugar (with the
+/// transactions replaced by the signed ones) or a file with one encoded
+/// transaction per line.
+fn load_transactions(path: &str) -> Result<Vec<(String, Transaction)>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Failed to read transactions file {}: {}", path, err))?;
+
+    if let Ok(exported) = serde_json::from_str::<Vec<ExportedTransaction>>(&content) {
+        return exported
+            .into_iter()
+            .map(|exported| {
+                Ok((
+                    exported.description,
+                    decode_transaction(&exported.transaction, exported.encoding)?,
+                ))
+            })
+            .collect();
+    }
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let transaction = decode_transaction(line, TxEncoding::Base58)
+                .or_else(|_| decode_transaction(line, TxEncoding::Base64))
+                .map_err(|_| anyhow!("Failed to decode transaction on line {}", index + 1))?;
+            Ok((format!("Transaction {}", index + 1), transaction))
+        })
+        .collect()
+}
+
+fn submit_transaction(rpc_client: &RpcClient, transaction: &Transaction) -> Result<Signature> {
+    let missing = transaction
+        .signatures
+        .iter()
+        .zip(transaction.message.account_keys.iter())
+        .filter(|(signature, _)| **signature == Signature::default())
+        .map(|(_, signer)| signer.to_string())
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(anyhow!("Missing signature(s) of {}", missing.join(", ")));
+    }
+
+    transaction.verify()?;
+
+    Ok(rpc_client.send_and_confirm_transaction(transaction)?)
+}
+
+#[test]
+fn test_load_transactions() {
+    use anchor_client::solana_sdk::{
+        hash::Hash, pubkey::Pubkey, signature::Keypair, system_instruction,
+    };
+
+    use crate::tx::encode_transaction;
+
+    let payer = Keypair::new();
+    let instruction = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        Hash::new_unique(),
+    );
+
+    let path = std::env::temp_dir().join(format!("sugar-tx-{}.txt", payer.pubkey()));
+    fs::write(
+        &path,
+        format!(
+            "{}\n\n{}\n",
+            encode_transaction(&transaction, TxEncoding::Base58).unwrap(),
+            encode_transaction(&transaction, TxEncoding::Base64).unwrap()
+        ),
+    )
+    .unwrap();
+
+    let transactions = load_transactions(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(transactions.len(), 2);
+
+    for (_, loaded) in transactions {
+        assert_eq!(loaded, transaction);
+        assert!(loaded.verify().is_ok());
+    }
+}
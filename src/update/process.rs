@@ -12,6 +12,7 @@ use crate::{
     candy_machine::{get_candy_machine_state, CANDY_MACHINE_ID},
     common::*,
     config::{data::ConfigData, parser::get_config_data},
+    tx::{get_tx_authority, send_or_export, TxExport},
     utils::{assert_correct_authority, spinner_with_style},
 };
 
@@ -23,9 +24,19 @@ pub struct UpdateArgs {
     pub config: String,
     pub candy_machine: Option<String>,
     pub priority_fee: u64,
+    pub export_tx: Option<TxExport>,
+    pub nonce: Option<Pubkey>,
 }
 
 pub fn process_update(args: UpdateArgs) -> Result<()> {
+    // the update and the authority transactions cannot share a nonce
+    if args.export_tx.is_some() && args.nonce.is_some() && args.new_authority.is_some() {
+        return Err(anyhow!(
+            "A durable nonce account covers a single pending transaction, so --nonce cannot be \
+            used with --new-authority when exporting transactions"
+        ));
+    }
+
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
     let client = setup_client(&sugar_config)?;
     let config_data = get_config_data(&args.config)?;
@@ -63,10 +74,8 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
 
     pb.finish_with_message("Done");
 
-    assert_correct_authority(
-        &sugar_config.keypair.pubkey(),
-        &candy_machine_state.authority,
-    )?;
+    let authority = get_tx_authority(&args.export_tx, sugar_config.keypair.pubkey());
+    assert_correct_authority(&authority, &candy_machine_state.authority)?;
 
     println!(
         "\n{} {}Updating configuration",
@@ -82,27 +91,41 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
         .instruction(priority_fee)
         .accounts(nft_accounts::Update {
             candy_machine: candy_pubkey,
-            authority: program.payer(),
+            authority,
         })
         .args(nft_instruction::Update {
             data: candy_machine_data,
         });
 
     let pb = spinner_with_style();
-    pb.set_message("Sending update transaction...");
-
-    let update_signature = builder.send()?;
+    pb.set_message(if args.export_tx.is_some() {
+        "Exporting update transaction..."
+    } else {
+        "Sending update transaction..."
+    });
+
+    let update_signature = send_or_export(
+        &program.rpc(),
+        &builder.instructions()?,
+        &sugar_config.keypair,
+        &authority,
+        &args.export_tx,
+        &args.nonce,
+        "Update candy machine",
+    )?;
 
-    pb.finish_with_message(format!(
-        "{} {}",
-        style("Update signature:").bold(),
-        update_signature
-    ));
+    if let Some(update_signature) = update_signature {
+        pb.finish_with_message(format!(
+            "{} {}",
+            style("Update signature:").bold(),
+            update_signature
+        ));
+    } else {
+        pb.finish_with_message("Update transaction exported");
+    }
 
     if let Some(new_authority) = args.new_authority {
         let pb = spinner_with_style();
-        pb.set_message("Sending update authority transaction...");
-
         let new_authority_pubkey = Pubkey::from_str(&new_authority)?;
 
         let priority_fee = ComputeBudgetInstruction::set_compute_unit_price(args.priority_fee);
@@ -112,18 +135,37 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
             .instruction(priority_fee)
             .accounts(nft_accounts::SetAuthority {
                 candy_machine: candy_pubkey,
-                authority: program.payer(),
+                authority,
             })
             .args(nft_instruction::SetAuthority {
                 new_authority: new_authority_pubkey,
             });
 
-        let authority_signature = builder.send()?;
-        pb.finish_with_message(format!(
-            "{} {}",
-            style("Authority signature:").bold(),
-            authority_signature
-        ));
+        pb.set_message(if args.export_tx.is_some() {
+            "Exporting update authority transaction..."
+        } else {
+            "Sending update authority transaction..."
+        });
+
+        let authority_signature = send_or_export(
+            &program.rpc(),
+            &builder.instructions()?,
+            &sugar_config.keypair,
+            &authority,
+            &args.export_tx,
+            &args.nonce,
+            "Set candy machine authority",
+        )?;
+
+        if let Some(authority_signature) = authority_signature {
+            pb.finish_with_message(format!(
+                "{} {}",
+                style("Authority signature:").bold(),
+                authority_signature
+            ));
+        } else {
+            pb.finish_with_message("Update authority transaction exported");
+        }
     }
 
     Ok(())
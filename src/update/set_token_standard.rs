@@ -151,6 +151,7 @@ pub fn process_set_token_stardard(args: SetTokenStandardArgs) -> Result<()> {
 
     pb.finish_and_clear();
     println!("{} {}", style("Signature:").bold(), sig);
+    push_output("signatures", sig.to_string());
 
     Ok(())
 }
@@ -2,7 +2,7 @@ use std::{cmp, fs, path::Path, sync::Arc};
 
 use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
 use async_trait::async_trait;
-use bundlr_sdk::{tags::Tag, Bundlr, Ed25519Signer as SolanaSigner};
+use bundlr_sdk::{tags::Tag, Bundlr};
 use clap::crate_version;
 use console::style;
 use solana_client::rpc_client::RpcClient;
@@ -15,6 +15,7 @@ use crate::{
     candy_machine::CANDY_MACHINE_ID,
     common::*,
     config::*,
+    signer::BundlrSigner,
     upload::{
         assets::{get_updated_metadata, AssetPair, DataType},
         uploader::{AssetInfo, ParallelUploader, Prepare, MOCK_URI_SIZE},
@@ -35,7 +36,7 @@ const HEADER_SIZE: u64 = 2_000;
 const MINIMUM_SIZE: u64 = 80_000;
 
 pub struct BundlrMethod {
-    pub client: Arc<Bundlr<SolanaSigner>>,
+    pub client: Arc<Bundlr<BundlrSigner>>,
     pub sugar_tag: Tag,
     pubkey: Pubkey,
     node: String,
@@ -60,9 +61,7 @@ impl BundlrMethod {
             BundlrMethod::get_bundlr_solana_address(&http_client, bundlr_node).await?;
 
         let bundlr_pubkey = Pubkey::from_str(&bundlr_address)?;
-        // get keypair as base58 string for Bundlr
-        let keypair = bs58::encode(sugar_config.keypair.to_bytes()).into_string();
-        let signer = SolanaSigner::from_base58(&keypair);
+        let signer = BundlrSigner(sugar_config.keypair.clone());
 
         let bundlr_client = Bundlr::new(
             bundlr_node.to_string(),
@@ -104,7 +103,7 @@ impl BundlrMethod {
         http_client: &HttpClient,
         bundlr_address: &Pubkey,
         node: &str,
-        payer: &Keypair,
+        payer: &SugarSigner,
         amount: u64,
     ) -> Result<Response> {
         let ix = system_instruction::transfer(&payer.pubkey(), bundlr_address, amount);
@@ -174,7 +173,7 @@ impl BundlrMethod {
     }
 
     async fn send(
-        client: Arc<Bundlr<SolanaSigner>>,
+        client: Arc<Bundlr<BundlrSigner>>,
         tag: Tag,
         asset_info: AssetInfo,
     ) -> Result<(String, String)> {
@@ -447,6 +447,9 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         .bold()
     );
 
+    set_output("uploaded", count);
+    set_output("total", asset_pairs.len());
+
     if count != asset_pairs.len() {
         let message = if !errors.is_empty() {
             let mut message = String::new();
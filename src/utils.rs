@@ -1,4 +1,6 @@
-use std::{ops::Deref, str::FromStr, thread::sleep, time::Duration};
+use std::{
+    collections::HashMap, ops::Deref, str::FromStr, sync::Mutex, thread::sleep, time::Duration,
+};
 
 pub use anchor_client::solana_sdk::hash::Hash;
 use anchor_client::{
@@ -23,7 +25,33 @@ use solana_client::{
 };
 use spl_token::state::{Account as SplAccount, Mint};
 
-use crate::{common::*, config::data::Cluster};
+use crate::{
+    common::*,
+    config::{data::Cluster, CandyGuardData, TokenAmount},
+    constants::TOKEN_2022_PROGRAM_ID,
+};
+
+/// Action to run before exiting when the command is interrupted.
+static INTERRUPT_HOOK: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+/// Sets the action to run if the command is interrupted, replacing any
+/// previous one.
+pub fn set_interrupt_hook(hook: Box<dyn Fn() + Send>) {
+    *INTERRUPT_HOOK.lock().unwrap() = Some(hook);
+}
+
+pub fn clear_interrupt_hook() {
+    INTERRUPT_HOOK.lock().unwrap().take();
+}
+
+/// Runs (and clears) the interrupt action, if any.
+pub fn run_interrupt_hook() {
+    let hook = INTERRUPT_HOOK.lock().unwrap().take();
+
+    if let Some(hook) = hook {
+        hook();
+    }
+}
 
 /// Hash for devnet cluster
 pub const DEVNET_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
@@ -68,6 +96,53 @@ pub fn check_spl_token<C: Deref<Target = impl Signer> + Clone>(
     }
 }
 
+/// Returns the decimals of a mint, which can be owned by either the spl-token or
+/// the token-2022 program.
+pub fn get_mint_decimals<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    mint: &Pubkey,
+) -> Result<u8> {
+    let account = program.rpc().get_account(mint)?;
+
+    if account.owner == Pubkey::from_str(TOKEN_2022_PROGRAM_ID)? {
+        // token-2022 mints share the spl-token layout, followed by extensions
+        let token_mint = account
+            .data
+            .get(..Mint::LEN)
+            .ok_or_else(|| anyhow!("Invalid token-2022 mint: {mint}"))
+            .and_then(|data| Ok(Mint::unpack_from_slice(data)?))?;
+        Ok(token_mint.decimals)
+    } else {
+        Ok(check_spl_token(program, &mint.to_string())?.decimals)
+    }
+}
+
+/// Converts the token amounts specified in UI units to base units, using the
+/// decimals of their mints.
+pub fn resolve_token_amounts<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    guards: &mut CandyGuardData,
+) -> Result<()> {
+    let mut decimals: HashMap<Pubkey, u8> = HashMap::new();
+
+    for (mint, amount) in guards.token_amounts_mut() {
+        if let TokenAmount::Ui(_) = amount {
+            let mint_decimals = match decimals.get(&mint) {
+                Some(mint_decimals) => *mint_decimals,
+                None => {
+                    let mint_decimals = get_mint_decimals(program, &mint)?;
+                    decimals.insert(mint, mint_decimals);
+                    mint_decimals
+                }
+            };
+
+            *amount = TokenAmount::Base(amount.to_base_units(mint_decimals)?);
+        }
+    }
+
+    Ok(())
+}
+
 /// Check that the mint token account is a valid account.
 pub fn check_spl_token_account<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
@@ -242,3 +317,12 @@ fn get_cm_creator_accounts(
 
     Ok(results)
 }
+
+/// Quotes the value if it contains a separator, quote or line break.
+pub fn escape_csv_value(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
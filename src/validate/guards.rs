@@ -0,0 +1,415 @@
+use std::{collections::HashSet, ops::Deref, str::FromStr};
+
+use anchor_client::{
+    solana_sdk::{pubkey::Pubkey, signature::Signer},
+    Program,
+};
+use anyhow::{anyhow, Result};
+use console::style;
+use mpl_candy_guard::state::MAX_LABEL_SIZE;
+use serde_json::Value;
+
+use crate::{
+    config::{CandyGuardData, EndDate, GuardSet, StartDate, TokenAmount},
+    constants::{CIVIC_NETWORK, ENCORE_NETWORK, WARNING_EMOJI},
+    utils::get_mint_decimals,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The configuration is invalid and must be fixed.
+    Error,
+    /// The configuration is valid, but likely not what was intended.
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardLint {
+    pub level: LintLevel,
+    /// Guard set where the issue was found ("default" or the group label).
+    pub location: String,
+    pub message: String,
+}
+
+impl GuardLint {
+    pub(crate) fn error(location: &str, message: String) -> Self {
+        Self {
+            level: LintLevel::Error,
+            location: location.to_string(),
+            message,
+        }
+    }
+
+    pub(crate) fn warning(location: &str, message: String) -> Self {
+        Self {
+            level: LintLevel::Warning,
+            location: location.to_string(),
+            message,
+        }
+    }
+}
+
+/// Checks the guards configuration for issues that would only surface at mint
+/// time. This does not require access to the chain.
+pub fn lint_guards(guards: &CandyGuardData) -> Result<Vec<GuardLint>> {
+    let mut lints = Vec::new();
+    let default = &guards.default;
+
+    lint_guard_set(default, "default", &mut lints);
+
+    let groups = match &guards.groups {
+        Some(groups) if !groups.is_empty() => groups,
+        _ => {
+            lint_dates(
+                default.start_date.as_ref(),
+                default.end_date.as_ref(),
+                "default",
+                &mut lints,
+            )?;
+            lint_payments(default, default, "default", &mut lints);
+            return Ok(lints);
+        }
+    };
+
+    let default_guards = enabled_guards(default)?;
+    let mut labels = HashSet::new();
+
+    for group in groups {
+        let label = group.label.as_str();
+
+        if label.is_empty() {
+            lints.push(GuardLint::error(
+                label,
+                "group label must not be empty".to_string(),
+            ));
+        } else if label.len() > MAX_LABEL_SIZE {
+            lints.push(GuardLint::error(
+                label,
+                format!(
+                    "group label exceeds the maximum of {MAX_LABEL_SIZE} characters ({})",
+                    label.len()
+                ),
+            ));
+        }
+
+        if !labels.insert(label) {
+            lints.push(GuardLint::error(
+                label,
+                "duplicated group label".to_string(),
+            ));
+        }
+
+        lint_guard_set(&group.guards, label, &mut lints);
+
+        // guards from the default set apply to every group, unless the group
+        // specifies the same guard
+        let shadowed: Vec<String> = enabled_guards(&group.guards)?
+            .into_iter()
+            .filter(|guard| default_guards.contains(guard))
+            .collect();
+
+        if !shadowed.is_empty() {
+            lints.push(GuardLint::warning(
+                label,
+                format!(
+                    "group overrides guard(s) from the default set: {}",
+                    shadowed.join(", ")
+                ),
+            ));
+        }
+
+        let (start_date, end_date) = group.guards.merged_dates(Some(default));
+        lint_dates(start_date, end_date, label, &mut lints)?;
+        lint_payments(&group.guards, default, label, &mut lints);
+    }
+
+    Ok(lints)
+}
+
+/// Checks that the token amounts of the guards fit the decimals of their
+/// mints. This requires access to the chain.
+pub fn lint_guard_tokens<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    guards: &CandyGuardData,
+) -> Result<Vec<GuardLint>> {
+    let mut lints = Vec::new();
+
+    for (location, guard, mint, amount) in guards.token_amounts() {
+        let decimals = get_mint_decimals(program, &mint)
+            .map_err(|err| anyhow!("Invalid mint for {guard} guard ({location}): {err}"))?;
+
+        match amount {
+            TokenAmount::Base(amount) => {
+                if let Some(message) = check_amount_decimals(*amount, decimals) {
+                    lints.push(GuardLint::warning(location, format!("{guard} {message}")));
+                }
+            }
+            TokenAmount::Ui(_) => match amount.to_base_units(decimals) {
+                Ok(0) => lints.push(GuardLint::warning(
+                    location,
+                    format!("{guard} amount is zero"),
+                )),
+                Ok(_) => (),
+                Err(err) => lints.push(GuardLint::error(location, format!("{guard} {err}"))),
+            },
+        }
+    }
+
+    Ok(lints)
+}
+
+/// Prints the guard issues found and returns an error if any of them is
+/// not a warning.
+pub fn report_guard_lints(lints: &[GuardLint]) -> Result<()> {
+    let mut errors = 0;
+
+    for lint in lints {
+        match lint.level {
+            LintLevel::Error => {
+                errors += 1;
+                println!(
+                    "{} {} {}",
+                    style("error:").red().bold(),
+                    style(format!("[{}]", lint.location)).bold(),
+                    lint.message
+                );
+            }
+            LintLevel::Warning => {
+                println!(
+                    "{}{} {} {}",
+                    WARNING_EMOJI,
+                    style("warning:").yellow().bold(),
+                    style(format!("[{}]", lint.location)).bold(),
+                    lint.message
+                );
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(anyhow!(
+            "Invalid guards configuration: {errors} error(s) found"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Runs all guard checks (including on-chain ones) and reports the issues found.
+pub fn validate_guards<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    guards: &CandyGuardData,
+) -> Result<()> {
+    let mut lints = lint_guards(guards)?;
+    lints.extend(lint_guard_tokens(program, guards)?);
+
+    report_guard_lints(&lints)
+}
+
+fn lint_guard_set(guard_set: &GuardSet, location: &str, lints: &mut Vec<GuardLint>) {
+    if let Some(bot_tax) = &guard_set.bot_tax {
+        if !bot_tax.last_instruction {
+            lints.push(GuardLint::warning(
+                location,
+                "botTax without lastInstruction will not tax transactions with \
+                 instructions after the mint"
+                    .to_string(),
+            ));
+        }
+    }
+
+    if let Some(gatekeeper) = &guard_set.gatekeeper {
+        let known = [CIVIC_NETWORK, ENCORE_NETWORK]
+            .iter()
+            .any(|network| Pubkey::from_str(network).ok() == Some(gatekeeper.gatekeeper_network));
+
+        if !known {
+            lints.push(GuardLint::warning(
+                location,
+                format!(
+                    "unknown gatekeeper network: {}",
+                    gatekeeper.gatekeeper_network
+                ),
+            ));
+        }
+    }
+}
+
+fn lint_dates(
+    start_date: Option<&StartDate>,
+    end_date: Option<&EndDate>,
+    location: &str,
+    lints: &mut Vec<GuardLint>,
+) -> Result<()> {
+    if let (Some(start_date), Some(end_date)) = (start_date, end_date) {
+        let start = start_date
+            .to_guard_format_with(Some(end_date))
+            .map_err(|err| anyhow!("Invalid startDate ({location}): {err}"))?
+            .date;
+        let end = end_date
+            .to_guard_format_with(Some(start_date))
+            .map_err(|err| anyhow!("Invalid endDate ({location}): {err}"))?
+            .date;
+
+        if start >= end {
+            lints.push(GuardLint::error(
+                location,
+                format!(
+                    "startDate ({}) is not before endDate ({})",
+                    start_date.date, end_date.date
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn lint_payments(
+    guard_set: &GuardSet,
+    default: &GuardSet,
+    location: &str,
+    lints: &mut Vec<GuardLint>,
+) {
+    let freeze = guard_set.freeze_sol_payment.is_some()
+        || guard_set.freeze_token_payment.is_some()
+        || default.freeze_sol_payment.is_some()
+        || default.freeze_token_payment.is_some();
+
+    let payment = guard_set.sol_payment.is_some()
+        || guard_set.token_payment.is_some()
+        || guard_set.token2022_payment.is_some()
+        || default.sol_payment.is_some()
+        || default.token_payment.is_some()
+        || default.token2022_payment.is_some();
+
+    if freeze && payment {
+        lints.push(GuardLint::error(
+            location,
+            "freeze and non-freeze payment guards are mixed in the same guard set".to_string(),
+        ));
+    }
+}
+
+fn check_amount_decimals(amount: u64, decimals: u8) -> Option<String> {
+    if amount == 0 {
+        return Some("amount is zero".to_string());
+    }
+
+    // numeric amounts are specified in base units, so anything smaller than
+    // one token was most likely meant to be in UI units
+    match 10u64.checked_pow(decimals as u32) {
+        Some(unit) if amount < unit => Some(format!(
+            "amount {amount} is less than one token (mint has {decimals} decimals)"
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the (camelCase) names of the guards enabled on the guard set.
+fn enabled_guards(guard_set: &GuardSet) -> Result<Vec<String>> {
+    let value = serde_json::to_value(guard_set)?;
+
+    Ok(match value {
+        Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, _)| key)
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+#[test]
+fn test_lint_guards_dates_and_labels() {
+    let guards: CandyGuardData = serde_json::from_str(
+        r#"{
+            "default": {
+                "startDate": { "date": "2022-10-20 18:00:00 +0000" }
+            },
+            "groups": [
+                {
+                    "label": "public1",
+                    "guards": {
+                        "endDate": { "date": "2022-10-19 18:00:00 +0000" }
+                    }
+                },
+                {
+                    "label": "public1",
+                    "guards": {}
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let lints = lint_guards(&guards).unwrap();
+    let messages: Vec<&str> = lints.iter().map(|l| l.message.as_str()).collect();
+
+    assert!(lints.iter().all(|l| l.level == LintLevel::Error));
+    assert_eq!(lints.len(), 4);
+    assert!(messages
+        .iter()
+        .any(|m| m.starts_with("group label exceeds")));
+    assert!(messages.contains(&"duplicated group label"));
+    assert!(messages.iter().any(|m| m.starts_with("startDate")));
+}
+
+#[test]
+fn test_lint_guards_payments_and_shadowing() {
+    let guards: CandyGuardData = serde_json::from_str(
+        r#"{
+            "default": {
+                "solPayment": {
+                    "value": 1,
+                    "destination": "11111111111111111111111111111111"
+                },
+                "botTax": { "value": 0.01, "lastInstruction": false }
+            },
+            "groups": [
+                {
+                    "label": "og",
+                    "guards": {
+                        "solPayment": {
+                            "value": 0.5,
+                            "destination": "11111111111111111111111111111111"
+                        }
+                    }
+                },
+                {
+                    "label": "frz",
+                    "guards": {
+                        "freezeSolPayment": {
+                            "value": 1,
+                            "destination": "11111111111111111111111111111111"
+                        }
+                    }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let lints = lint_guards(&guards).unwrap();
+
+    assert!(lints
+        .iter()
+        .any(|l| l.level == LintLevel::Warning && l.message.starts_with("botTax")));
+    assert!(lints
+        .iter()
+        .any(|l| l.location == "og" && l.message.contains("solPayment")));
+    assert!(lints
+        .iter()
+        .any(|l| l.location == "frz" && l.level == LintLevel::Error));
+    assert!(!lints
+        .iter()
+        .any(|l| l.location == "og" && l.level == LintLevel::Error));
+}
+
+#[test]
+fn test_check_amount_decimals() {
+    assert!(check_amount_decimals(0, 0).is_some());
+    assert!(check_amount_decimals(1, 0).is_none());
+    assert!(check_amount_decimals(5, 9).is_some());
+    assert!(check_amount_decimals(1_000_000_000, 9).is_none());
+}
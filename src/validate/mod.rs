@@ -1,11 +1,13 @@
 pub mod errors;
 pub mod format;
+pub mod guards;
 pub mod helpers;
 pub mod parser;
 pub mod process;
 
 pub use errors::*;
 pub use format::*;
+pub use guards::*;
 pub use helpers::*;
 pub use parser::*;
 pub use process::*;
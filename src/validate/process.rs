@@ -10,19 +10,28 @@ use dialoguer::{theme::ColorfulTheme, Confirm};
 use glob::glob;
 use rayon::prelude::*;
 
-use crate::{common::*, utils::*, validate::*};
+use crate::{common::*, config::get_config_data, utils::*, validate::*};
 
 pub struct ValidateArgs {
     pub assets_dir: String,
+    pub config: String,
     pub strict: bool,
     pub skip_collection_prompt: bool,
 }
 
 pub fn process_validate(args: ValidateArgs) -> Result<()> {
+    // the guards configuration is only checked if the config file is present
+    let guards = if Path::new(&args.config).exists() {
+        get_config_data(&args.config)?.guards
+    } else {
+        None
+    };
+    let total_steps = if guards.is_some() { 2 } else { 1 };
+
     // loading assets
     println!(
         "{} {}Loading assets",
-        style("[1/1]").bold().dim(),
+        style(format!("[1/{total_steps}]")).bold().dim(),
         ASSETS_EMOJI
     );
 
@@ -145,6 +154,16 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
         ));
     }
 
+    if let Some(guards) = guards {
+        println!(
+            "\n{} {}Checking guards configuration",
+            style(format!("[2/{total_steps}]")).bold().dim(),
+            GUARD_EMOJI
+        );
+
+        report_guard_lints(&lint_guards(&guards)?)?;
+    }
+
     let message = "Validation complete, your metadata file(s) look good.";
     info!("{message}");
     println!("\n{message}");
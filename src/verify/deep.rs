@@ -0,0 +1,179 @@
+use std::{fs, sync::Arc, time::Duration};
+
+use futures::future::join_all;
+use mpl_candy_machine_core::ConfigLine;
+use reqwest::StatusCode;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use crate::{common::*, utils::progress_bar_with_style};
+
+// number of metadata files checked in parallel
+const DEEP_CONCURRENCY: usize = 20;
+
+// timeout of each metadata (and image) request
+const REQUEST_TIMEOUT: u64 = 30;
+
+/// Result of checking the metadata of a config line.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataCheck {
+    pub index: u32,
+    pub name: String,
+    pub uri: String,
+    pub image: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Fetches the metadata of each config line and checks that the JSON parses,
+/// its name matches the config line and its image link resolves.
+pub async fn check_metadata(config_lines: Vec<(u32, ConfigLine)>) -> Result<Vec<MetadataCheck>> {
+    let http_client = HttpClient::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT))
+        .build()?;
+    let semaphore = Arc::new(Semaphore::new(DEEP_CONCURRENCY));
+    let pb = progress_bar_with_style(config_lines.len() as u64);
+    pb.set_message("Checking metadata...");
+
+    let mut tasks = Vec::new();
+
+    for (index, config_line) in config_lines {
+        let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+        let http_client = http_client.clone();
+        let pb = pb.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = permit;
+            let check = check_config_line(&http_client, index, config_line).await;
+            pb.inc(1);
+            check
+        }));
+    }
+
+    let mut checks = Vec::new();
+
+    for result in join_all(tasks).await {
+        checks.push(result?);
+    }
+
+    pb.finish_and_clear();
+    checks.sort_by_key(|check| check.index);
+
+    Ok(checks)
+}
+
+/// Writes the checks to the report file.
+pub fn write_report(checks: &[MetadataCheck], path: &str) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(checks)?)
+        .map_err(|err| anyhow!("Failed to write verify report {}: {}", path, err))
+}
+
+async fn check_config_line(
+    http_client: &HttpClient,
+    index: u32,
+    config_line: ConfigLine,
+) -> MetadataCheck {
+    let mut check = MetadataCheck {
+        index,
+        name: config_line.name,
+        uri: config_line.uri,
+        image: None,
+        errors: Vec::new(),
+    };
+
+    let metadata = match fetch_json(http_client, &check.uri).await {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            check.errors.push(err.to_string());
+            return check;
+        }
+    };
+
+    check.errors = check_metadata_json(&check.name, &metadata);
+    check.image = metadata
+        .get("image")
+        .and_then(|image| image.as_str())
+        .map(|image| image.to_string());
+
+    if let Some(image) = &check.image {
+        if let Err(err) = check_link(http_client, image).await {
+            check.errors.push(err.to_string());
+        }
+    }
+
+    check
+}
+
+async fn fetch_json(http_client: &HttpClient, uri: &str) -> Result<Value> {
+    let response = http_client
+        .get(uri)
+        .send()
+        .await
+        .map_err(|err| anyhow!("Failed to fetch metadata: {}", err))?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to fetch metadata: {}", response.status()));
+    }
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|err| anyhow!("Invalid metadata JSON: {}", err))
+}
+
+/// Checks that the link resolves, using a GET request if HEAD is not allowed.
+async fn check_link(http_client: &HttpClient, link: &str) -> Result<()> {
+    let mut response = http_client.head(link).send().await;
+
+    if matches!(&response, Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED) {
+        response = http_client.get(link).send().await;
+    }
+
+    match response {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(anyhow!(
+            "Image link does not resolve: {}",
+            response.status()
+        )),
+        Err(err) => Err(anyhow!("Image link does not resolve: {}", err)),
+    }
+}
+
+/// Returns the errors of the metadata JSON of a config line.
+fn check_metadata_json(name: &str, metadata: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    match metadata.get("name").and_then(|name| name.as_str()) {
+        Some(metadata_name) if metadata_name == name => (),
+        Some(metadata_name) => errors.push(format!(
+            "Name mismatch (expected='{name}', found='{metadata_name}')"
+        )),
+        None => errors.push("Missing name in metadata".to_string()),
+    }
+
+    match metadata.get("image").and_then(|image| image.as_str()) {
+        Some(image) if !image.is_empty() => (),
+        _ => errors.push("Missing image link in metadata".to_string()),
+    }
+
+    errors
+}
+
+#[test]
+fn test_check_metadata_json() {
+    let metadata = json!({ "name": "Sugar #1", "image": "https://arweave.net/1.png" });
+    assert!(check_metadata_json("Sugar #1", &metadata).is_empty());
+
+    let errors = check_metadata_json("Sugar #2", &metadata);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Name mismatch"));
+
+    let errors = check_metadata_json("Sugar #1", &json!({ "image": "" }));
+    assert_eq!(
+        errors,
+        vec![
+            "Missing name in metadata".to_string(),
+            "Missing image link in metadata".to_string()
+        ]
+    );
+}
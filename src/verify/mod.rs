@@ -1,5 +1,7 @@
+pub mod deep;
 pub mod errors;
 pub mod process;
 
+pub use deep::*;
 pub use errors::*;
 pub use process::*;
@@ -1,26 +1,26 @@
-use std::{thread, time::Duration};
-
 use anchor_lang::AccountDeserialize;
 use borsh::BorshDeserialize;
 use console::style;
-use mpl_candy_machine_core::{constants::HIDDEN_SECTION, CandyMachine};
+use mpl_candy_machine_core::{CandyMachine, ConfigLine};
 use mpl_token_metadata::state::Metadata;
 
 use crate::{
     cache::*,
-    candy_machine::CANDY_MACHINE_ID,
+    candy_machine::{get_config_lines, CANDY_MACHINE_ID},
     common::*,
     config::Cluster,
-    constants::{CANDY_EMOJI, PAPER_EMOJI},
+    constants::{CANDY_EMOJI, LOOKING_GLASS_EMOJI, PAPER_EMOJI},
     pdas::find_metadata_pda,
     utils::*,
-    verify::VerifyError,
+    verify::{check_metadata, write_report, VerifyError},
 };
 
 pub struct VerifyArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
+    pub deep: bool,
+    pub report: String,
 }
 
 #[derive(Debug)]
@@ -29,7 +29,7 @@ pub struct OnChainItem {
     pub uri: String,
 }
 
-pub fn process_verify(args: VerifyArgs) -> Result<()> {
+pub async fn process_verify(args: VerifyArgs) -> Result<()> {
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
 
     // loads the cache file (this needs to have been created by
@@ -48,9 +48,11 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
         return Err(CacheError::CacheFileNotFound(args.cache).into());
     }
 
+    let total_steps = if args.deep { 3 } else { 2 };
+
     println!(
         "{} {}Loading candy machine",
-        style("[1/2]").bold().dim(),
+        style(format!("[1/{total_steps}]")).bold().dim(),
         CANDY_EMOJI
     );
 
@@ -83,76 +85,40 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
 
     println!(
         "\n{} {}Verification",
-        style("[2/2]").bold().dim(),
+        style(format!("[2/{total_steps}]")).bold().dim(),
         PAPER_EMOJI
     );
 
+    let mut config_lines = Vec::new();
+
     if candy_machine.data.hidden_settings.is_some() {
         // nothing else to do, there are no config lines in a candy machine
         // with hidden settings
         println!("\nHidden settings enabled. No config items to verify.");
-    } else if let Some(config_line_settings) = &candy_machine.data.config_line_settings {
+    } else if candy_machine.data.config_line_settings.is_some() {
         let num_items = candy_machine.data.items_available;
-        let cache_items = &mut cache.items;
-        let mut errors = Vec::new();
 
         println!("Verifying {num_items} config line(s): (Ctrl+C to abort)");
-        let pb = progress_bar_with_style(num_items);
-        // sleeps for a about 1 second
-        let step: u64 = if num_items > 0 {
-            1_000_000u64 / num_items
-        } else {
-            0
-        };
+        let pb = spinner_with_style();
+        pb.set_message("Decoding config lines...");
 
-        let line_size = candy_machine.data.get_config_line_size();
-        let name_length = config_line_settings.name_length as usize;
-        let uri_length = config_line_settings.uri_length as usize;
-
-        for i in 0..num_items {
-            let name_start = HIDDEN_SECTION + STRING_LEN_SIZE + line_size * (i as usize);
-            let name_end = name_start + name_length;
-
-            let uri_start = name_end;
-            let uri_end = uri_start + uri_length;
-
-            let name_error = format!("Failed to decode name for item {i}");
-            let name = String::from_utf8(data[name_start..name_end].to_vec())
-                .expect(&name_error)
-                .trim_matches(char::from(0))
-                .to_string();
-
-            let uri_error = format!("Failed to decode uri for item {i}");
-            let uri = String::from_utf8(data[uri_start..uri_end].to_vec())
-                .expect(&uri_error)
-                .trim_matches(char::from(0))
-                .to_string();
-
-            let on_chain_item = OnChainItem {
-                name: config_line_settings.prefix_name.to_string() + &name,
-                uri: config_line_settings.prefix_uri.to_string() + &uri,
-            };
-            let cache_item = cache_items
-                .get_mut(&i.to_string())
-                .expect("Failed to get item from config.");
-
-            if let Err(err) = items_match(cache_item, &on_chain_item) {
-                cache_item.on_chain = false;
-                errors.push((i.to_string(), err.to_string()));
-            }
+        config_lines = get_config_lines(&candy_machine, &data)?;
+        let status = sync_config_lines(&mut cache.items, &config_lines);
 
-            pb.inc(1);
-            thread::sleep(Duration::from_micros(step));
+        if status.changed {
+            cache.sync_file()?;
         }
 
-        if !errors.is_empty() {
+        set_output("verified", status.matched);
+        set_output("invalid", status.errors.len());
+
+        if !status.errors.is_empty() {
             pb.abandon_with_message(format!("{}", style("Verification failed ").red().bold()));
-            cache.sync_file()?;
 
-            let total = errors.len();
+            let total = status.errors.len();
             println!("\nInvalid items found: ");
 
-            for e in errors {
+            for e in status.errors {
                 println!("- Item {}: {}", e.0, e.1);
             }
             println!("\nCache updated - re-run `deploy`.");
@@ -205,6 +171,57 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
         }
     }
 
+    if args.deep {
+        println!(
+            "\n{} {}Checking metadata",
+            style(format!("[3/{total_steps}]")).bold().dim(),
+            LOOKING_GLASS_EMOJI
+        );
+
+        if candy_machine.data.hidden_settings.is_some() {
+            println!("\nHidden settings enabled. No metadata to check.");
+        } else {
+            let checks = check_metadata(
+                config_lines
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(index, line)| line.map(|line| (index as u32, line)))
+                    .collect(),
+            )
+            .await?;
+            write_report(&checks, &args.report)?;
+
+            let failed = checks
+                .iter()
+                .filter(|check| !check.errors.is_empty())
+                .collect::<Vec<_>>();
+
+            set_output("metadataChecked", checks.len());
+            set_output("metadataFailed", failed.len());
+            set_output("report", &args.report);
+
+            println!(
+                "{}/{} metadata file(s) valid, report saved to {}",
+                checks.len() - failed.len(),
+                checks.len(),
+                args.report
+            );
+
+            if !failed.is_empty() {
+                println!("\nInvalid metadata found: ");
+
+                for check in &failed {
+                    println!("- Item {}: {}", check.index, check.errors.join(", "));
+                }
+
+                return Err(anyhow!(
+                    "{} item(s) failed the metadata verification.",
+                    failed.len()
+                ));
+            }
+        }
+    }
+
     let cluster = match get_cluster(program.rpc())? {
         Cluster::Devnet => "devnet-alpha",
         Cluster::Mainnet => "mainnet-alpha",
@@ -224,6 +241,62 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
     Ok(())
 }
 
+/// Outcome of syncing the cache with the config lines written on-chain.
+#[derive(Debug, Default)]
+pub struct ConfigLineStatus {
+    /// Number of cache items matching their on-chain config line.
+    pub matched: usize,
+    /// Cache items missing or mismatched on-chain, with the reason.
+    pub errors: Vec<(String, String)>,
+    /// Whether any `on_chain` flag of the cache was updated.
+    pub changed: bool,
+}
+
+/// Rebuilds the `on_chain` flags of the cache items from the config lines
+/// written on-chain: an item is on-chain only if its config line is written
+/// and matches the cache.
+pub fn sync_config_lines(
+    cache_items: &mut CacheItems,
+    config_lines: &[Option<ConfigLine>],
+) -> ConfigLineStatus {
+    let mut status = ConfigLineStatus::default();
+
+    for (key, cache_item) in cache_items.0.iter_mut() {
+        // the collection item (-1) is not a config line
+        let index = match key.parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => continue,
+        };
+
+        let result = match config_lines.get(index) {
+            Some(Some(config_line)) => items_match(
+                cache_item,
+                &OnChainItem {
+                    name: config_line.name.clone(),
+                    uri: config_line.uri.clone(),
+                },
+            )
+            .map_err(|err| err.to_string()),
+            Some(None) => Err("config line not written on-chain".to_string()),
+            None => Err("index exceeds the number of items available".to_string()),
+        };
+
+        let on_chain = result.is_ok();
+
+        if cache_item.on_chain != on_chain {
+            cache_item.on_chain = on_chain;
+            status.changed = true;
+        }
+
+        match result {
+            Ok(()) => status.matched += 1,
+            Err(err) => status.errors.push((key.clone(), err)),
+        }
+    }
+
+    status
+}
+
 fn items_match(cache_item: &CacheItem, on_chain_item: &OnChainItem) -> Result<()> {
     if cache_item.name != on_chain_item.name {
         return Err(VerifyError::Mismatch(
@@ -1,4 +1,4 @@
-use std::{ops::Deref, rc::Rc, str::FromStr};
+use std::{ops::Deref, rc::Rc, str::FromStr, sync::Arc};
 
 pub use anchor_client::{
     solana_sdk::{
@@ -24,8 +24,12 @@ use solana_client::{
 use crate::{
     candy_machine::CANDY_MACHINE_ID,
     common::*,
+    mints::{
+        discover, get_authority_history_candy_machines, mint_history_cache_path, MintDiscovery,
+    },
     parse::parse_sugar_errors,
     setup::{setup_client, sugar_setup},
+    tx::{get_tx_authority, send_or_export, TxExport},
     utils::*,
 };
 
@@ -33,9 +37,13 @@ pub struct WithdrawArgs {
     pub candy_machine: Option<String>,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub cache: String,
     pub list: bool,
     pub authority: Option<String>,
     pub priority_fee: u64,
+    pub export_tx: Option<TxExport>,
+    pub nonce: Option<Pubkey>,
+    pub discovery: MintDiscovery,
 }
 
 #[derive(Debug)]
@@ -44,7 +52,7 @@ struct WithdrawError {
     error_message: String,
 }
 
-pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
+pub async fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     // (1) Setting up connection
 
     println!(
@@ -56,10 +64,47 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let (program, payer, authority) = setup_withdraw(args.keypair, args.rpc_url, args.authority)?;
+    let (program, signer, authority) = setup_withdraw(args.keypair, args.rpc_url, args.authority)?;
 
     pb.finish_with_message("Connected");
 
+    let payer = signer.pubkey();
+
+    if args.export_tx.is_some() {
+        // only a single candy machine can be drained by an exported transaction
+        let candy_machine = match &args.candy_machine {
+            Some(candy_machine) => Pubkey::from_str(candy_machine)?,
+            None => {
+                return Err(anyhow!(
+                    "A candy machine id is required to export a withdraw transaction"
+                ))
+            }
+        };
+
+        println!(
+            "\n{} {}Exporting withdraw transaction",
+            style("[2/2]").bold().dim(),
+            WITHDRAW_EMOJI
+        );
+
+        let pb = spinner_with_style();
+        pb.set_message("Exporting withdraw transaction...");
+
+        do_withdraw(
+            Rc::new(program),
+            candy_machine,
+            &signer,
+            get_tx_authority(&args.export_tx, payer),
+            args.priority_fee,
+            &args.export_tx,
+            &args.nonce,
+        )?;
+
+        pb.finish_with_message("Done");
+
+        return Ok(());
+    }
+
     // if --authority is specified and it does not match the keypair,
     // then we cannot withdraw
     let list = args.list || (payer != authority);
@@ -84,7 +129,15 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
             let pb = spinner_with_style();
             pb.set_message("Draining candy machine...");
 
-            do_withdraw(Rc::new(program), candy_machine, payer, args.priority_fee)?;
+            do_withdraw(
+                Rc::new(program),
+                candy_machine,
+                &signer,
+                payer,
+                args.priority_fee,
+                &None,
+                &args.nonce,
+            )?;
 
             pb.finish_with_message("Done");
         }
@@ -109,9 +162,21 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
             pb.set_message("Looking up candy machines...");
 
             let program = Rc::new(program);
-            let accounts = program
-                .rpc()
-                .get_program_accounts_with_config(&program.id(), config)?;
+            let accounts = discover(
+                args.discovery,
+                Some(|| {
+                    program
+                        .rpc()
+                        .get_program_accounts_with_config(&program.id(), config)
+                        .map_err(|err| anyhow!(err))
+                }),
+                get_authority_history_candy_machines(
+                    Arc::new(program.rpc()),
+                    &authority,
+                    &mint_history_cache_path(&args.cache),
+                ),
+            )
+            .await?;
 
             pb.finish_and_clear();
 
@@ -169,16 +234,24 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
 
                     accounts.iter().for_each(|account| {
                         let (candy_machine, _account) = account;
-                        do_withdraw(program.clone(), *candy_machine, payer, args.priority_fee)
-                            .unwrap_or_else(|e| {
-                                not_drained += 1;
-                                error!("Error: {}", e);
-                                let error_message = parse_sugar_errors(&e.to_string());
-                                error_messages.push(WithdrawError {
-                                    candy_machine: candy_machine.to_string(),
-                                    error_message,
-                                });
+                        do_withdraw(
+                            program.clone(),
+                            *candy_machine,
+                            &signer,
+                            payer,
+                            args.priority_fee,
+                            &None,
+                            &args.nonce,
+                        )
+                        .unwrap_or_else(|e| {
+                            not_drained += 1;
+                            error!("Error: {}", e);
+                            let error_message = parse_sugar_errors(&e.to_string());
+                            error_messages.push(WithdrawError {
+                                candy_machine: candy_machine.to_string(),
+                                error_message,
                             });
+                        });
                         pb.inc(1);
                     });
 
@@ -215,7 +288,7 @@ fn setup_withdraw(
     keypair: Option<String>,
     rpc_url: Option<String>,
     authority_opt: Option<String>,
-) -> Result<(Program<Rc<Keypair>>, Pubkey, Pubkey)> {
+) -> Result<(Program<Rc<SugarSigner>>, SugarSigner, Pubkey)> {
     let sugar_config = sugar_setup(keypair, rpc_url)?;
     let client = setup_client(&sugar_config)?;
     let program = client.program(CANDY_MACHINE_ID);
@@ -226,25 +299,37 @@ fn setup_withdraw(
         payer
     };
 
-    Ok((program, payer, authority))
+    Ok((program, sugar_config.keypair, authority))
 }
 
 fn do_withdraw<C: Deref<Target = impl Signer> + Clone>(
     program: Rc<Program<C>>,
     candy_machine: Pubkey,
+    signer: &dyn Signer,
     payer: Pubkey,
     priority_fee: u64,
+    export_tx: &Option<TxExport>,
+    nonce: &Option<Pubkey>,
 ) -> Result<()> {
     let priority_fee_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
-    program
+    let builder = program
         .request()
         .instruction(priority_fee_ix)
         .accounts(nft_accounts::Withdraw {
             candy_machine,
             authority: payer,
         })
-        .args(nft_instruction::Withdraw {})
-        .send()?;
+        .args(nft_instruction::Withdraw {});
+
+    send_or_export(
+        &program.rpc(),
+        &builder.instructions()?,
+        signer,
+        &payer,
+        export_tx,
+        nonce,
+        &format!("Withdraw candy machine {candy_machine}"),
+    )?;
 
     Ok(())
 }